@@ -1,134 +1,1050 @@
-use crate::data::{discover_ports, PortInfo};
-use crate::monitor::{spawn_chart_monitor, PortHistory};
-use crate::handler::handle_key_event;
+use crate::data::{discover_ports, PortInfo, PortType, PortTypeFilter};
+use crate::monitor::{spawn_chart_monitor, Aggregation, spawn_error_monitor, spawn_queue_monitor, MonitorConfig, MonitorControl, PortHistory};
+use crate::handler::{handle_key_event, handle_mouse_event};
+use crate::metrics;
+use crate::record::{Recorder, RecordSummary};
 use crate::tui::Tui;
-use crate::ui;
-use crate::Args;
+use crate::theme::{Theme, ThemeName};
+use crate::ui::{self, SpeedUnit};
+use crate::config::{ColorOverrides, Config};
 use anyhow::{Context, Result};
 use crossterm::event::{Event, EventStream};
 use futures::StreamExt;
+use ratatui::layout::Rect;
 use ratatui::widgets::ScrollbarState; // 新增引用
 use std::sync::{Arc, RwLock};
-use std::time::Duration;
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// 运行时重新扫描 sysfs 的间隔
+pub const REDISCOVER_INTERVAL: Duration = Duration::from_secs(3);
+
+/// 退出时等待监控线程结束的上限
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// 状态栏提示 (如快照已保存) 的显示时长
+pub const NOTICE_DURATION: Duration = Duration::from_secs(3);
+
+/// 界面刷新率 (帧/秒) 的默认值与允许范围，与采样、提交间隔无关
+pub const DEFAULT_FPS: u32 = 10;
+pub const MIN_FPS: u32 = 1;
+pub const MAX_FPS: u32 = 60;
+
+/// 用户选择监控的端口范围
+#[derive(Debug, Clone)]
+pub enum PortSelection {
+    /// `--all`：监控所有发现的端口，包括运行期间新出现的
+    All,
+    /// `--interfaces`：只监控指定名称的端口，尚未出现的会在出现后补上
+    Named(Vec<String>),
+}
+
+impl PortSelection {
+    fn matches(&self, port: &PortInfo) -> bool {
+        match self {
+            PortSelection::All => true,
+            PortSelection::Named(names) => names.contains(&port.name),
+        }
+    }
+}
+
+/// 端口相对告警阈值的状态
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AlertLevel {
+    Normal,
+    /// RX 或 TX 高于上限 (链路饱和)
+    High,
+    /// RX 与 TX 都低于下限 (流量意外中断)
+    Low,
+}
+
+/// 一组告警阈值 (Bytes/s)，`None` 表示不检查
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct AlertThresholds {
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+}
+
+/// 全局阈值与按端口名覆盖的阈值
+#[derive(Debug, Default, Clone)]
+pub struct Alerts {
+    pub default: AlertThresholds,
+    pub ports: std::collections::HashMap<String, AlertThresholds>,
+}
+
+impl Alerts {
+    /// 端口单独配置的阈值逐项覆盖全局阈值
+    pub fn for_port(&self, name: &str) -> AlertThresholds {
+        match self.ports.get(name) {
+            Some(port) => AlertThresholds {
+                high: port.high.or(self.default.high),
+                low: port.low.or(self.default.low),
+            },
+            None => self.default,
+        }
+    }
+}
 
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub enum ViewMode {
     Table,
     Chart,
+    Packets,
+    Errors,
+    /// 每队列速率 (仅 `-q` 时可切换到)
+    Queues,
+    /// 紧凑的 sparkline 视图，端口较多时使用
+    Sparkline,
+}
+
+/// 网格中端口的排列方式
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SortMode {
+    /// 按端口名称，运行中新出现的端口同样按名称插入
+    Name,
+    /// 以下按最新一个点降序 (最忙的在前)，速率相同时按名称
+    Rx,
+    Tx,
+    Total,
+}
+
+impl SortMode {
+    pub fn next(self) -> Self {
+        match self {
+            SortMode::Name => SortMode::Rx,
+            SortMode::Rx => SortMode::Tx,
+            SortMode::Tx => SortMode::Total,
+            SortMode::Total => SortMode::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::Name => "Name",
+            SortMode::Rx => "RX",
+            SortMode::Tx => "TX",
+            SortMode::Total => "Total",
+        }
+    }
+
+    /// 排序依据的速率 (Bytes/s)
+    fn key(self, history: &PortHistory) -> f64 {
+        let (rx, tx) = latest_rates(history);
+        match self {
+            SortMode::Name => 0.0,
+            SortMode::Rx => rx,
+            SortMode::Tx => tx,
+            SortMode::Total => rx + tx,
+        }
+    }
+}
+
+/// 端口最新一个点的 (RX, TX) 速率 (Bytes/s)
+///
+/// 断线端口的最后一点已过时，按 0 处理；排序与总吞吐使用同一规则。
+fn latest_rates(history: &PortHistory) -> (f64, f64) {
+    if history.stale {
+        return (0.0, 0.0);
+    }
+    (
+        history.rx_data.back().map(|(_, v)| *v).unwrap_or(0.0),
+        history.tx_data.back().map(|(_, v)| *v).unwrap_or(0.0),
+    )
 }
 
 pub struct App {
     pub should_quit: bool,
     pub view_mode: ViewMode,
+    pub unit: SpeedUnit,
+    /// 是否显示相对链路速率的利用率
+    pub show_utilization: bool,
+    /// 是否显示快捷键帮助浮层；打开时大部分按键被忽略
+    pub show_help: bool,
+    /// 状态栏中的一次性提示及其产生时刻，显示 `NOTICE_DURATION` 后消失
+    pub notice: Option<(String, Instant)>,
     pub version: String,
     
     // 核心数据源
     pub histories: Vec<Arc<RwLock<PortHistory>>>,
+    /// 与 `histories` 一一对应的端口名，无需加锁即可读取
+    port_names: Vec<String>,
+    pub monitor_config: MonitorConfig,
+    pub selection: PortSelection,
+    /// `--type`：只监控指定类型的端口，对运行期间新发现的端口同样生效
+    pub type_filter: PortTypeFilter,
+    pub metrics_port: Option<u16>,
+    /// 界面刷新率 (已限制在 MIN_FPS..=MAX_FPS)；按键事件不受其影响，随到随处理
+    pub fps: u32,
+    /// 当前配色，由 `theme_name` 预设叠加配置文件中的颜色覆盖得到
+    pub theme: Theme,
+    pub theme_name: ThemeName,
+    /// 配置文件中的颜色覆盖，切换主题时重新叠加
+    pub colors: ColorOverrides,
+    /// `--alert-high` / `--alert-low` 与配置文件中的告警阈值
+    pub alerts: Alerts,
+    /// `-q`：为以太网端口启动每队列监控，并启用队列视图
+    pub monitor_queues: bool,
+    /// `--include-virtual`：发现时包含 bond / VLAN / 网桥等虚拟接口，重新扫描时同样生效
+    pub include_virtual: bool,
+    /// 所有端口的监控线程，退出时统一 join
+    monitor_threads: Vec<std::thread::JoinHandle<()>>,
+    /// `--record` 的 CSV 写入端，退出时由 `finish_recording` 收尾
+    recorder: Option<Recorder>,
+    /// 端口列表变化时通知后台消费者 (metrics 服务)
+    histories_tx: watch::Sender<Vec<Arc<RwLock<PortHistory>>>>,
 
     // --- 新增：滚动状态 ---
     pub vertical_scroll: usize, // 当前第一行显示的是第几个网卡
     pub scroll_state: ScrollbarState, // Ratatui 的滚动条状态
+
+    /// 网格排列方式
+    pub sort_mode: SortMode,
+    /// 网格中第 i 个位置显示的端口 (histories 下标)，每帧按 `sort_mode` 重新计算；
+    /// 只重排下标，`histories` 本身与监控线程的对应关系不变
+    pub display_order: Vec<usize>,
+    /// 光标所在的端口 (histories 下标，重新排序时光标跟随端口而不是位置)
+    pub selected: usize,
+    /// 全屏详情视图中的端口，None 表示网格视图
+    pub focused: Option<usize>,
+    /// 上一帧网格区域，用于鼠标点击的命中测试
+    pub grid_area: Rect,
 }
 
 impl App {
-    pub async fn try_new(args: Args) -> Result<Self> {
+    pub async fn try_new(settings: Config) -> Result<Self> {
         let version = env!("CARGO_PKG_VERSION").to_string();
 
-        let initial_ports = discover_ports(false)
+        let mut config = MonitorConfig {
+            sysfs_root: settings.sysfs_root.clone(),
+            sample_interval: Duration::from_micros(settings.sample_interval_us),
+            commit_interval: Duration::from_millis(settings.commit_interval_ms),
+            history_points: settings.history_points,
+            control: Arc::new(MonitorControl::new(settings.aggregate)),
+            recorder: None,
+        };
+        config.validate()?;
+
+        let initial_ports = discover_ports(&config.sysfs_root, settings.include_virtual)
             .await
             .context("Failed to discover network ports.")?;
 
-        if initial_ports.is_empty() {
+        let selection = settings.selection;
+        let type_filter = settings.port_type;
+
+        // 点名的端口可能稍后才出现，只有 --all 在一个端口都没有时直接失败
+        if initial_ports.is_empty() && matches!(selection, PortSelection::All) {
             anyhow::bail!("No physical RDMA or Ethernet interfaces found.");
         }
 
-        let selected_ports: Vec<PortInfo> = if args.mode.all {
-            initial_ports
-        } else if let Some(iface_names) = args.mode.interfaces {
-            initial_ports
-                .into_iter()
-                .filter(|p| iface_names.contains(&p.name))
-                .collect()
-        } else {
-            unreachable!();
-        };
+        // 显式点名却被 --type 排除的端口，提示用户而不是静默忽略
+        let mut warnings = Vec::new();
+        if let PortSelection::Named(names) = &selection {
+            for port in initial_ports.iter().filter(|p| names.contains(&p.name) && !type_filter.matches(p.port_type)) {
+                let warning = format!(
+                    "Warning: {} ({}) is excluded by --type {}.",
+                    port.name,
+                    port.port_type.as_str(),
+                    type_filter.as_str()
+                );
+                eprintln!("{}", warning);
+                warnings.push(warning);
+            }
+        }
 
-        if selected_ports.is_empty() {
-            anyhow::bail!("No valid interfaces selected to monitor.");
+        // 尚未出现的点名端口 (热插拔、驱动稍后加载) 由 rediscover 在出现后补上
+        let mut waiting = false;
+        if let PortSelection::Named(names) = &selection {
+            let missing: Vec<&str> = names
+                .iter()
+                .filter(|n| !initial_ports.iter().any(|p| &p.name == *n))
+                .map(String::as_str)
+                .collect();
+            if !missing.is_empty() {
+                let notice = format!("Waiting for {} to appear.", missing.join(", "));
+                eprintln!("{}", notice);
+                warnings.push(notice);
+                waiting = true;
+            }
         }
 
-        let mut histories = Vec::new();
-        for port in selected_ports {
-            let history = Arc::new(RwLock::new(
-                PortHistory::new(port.name.clone(), port.port_type)
-            ));
-            spawn_chart_monitor(
-                port.device_path_part, 
-                port.port_num_part, 
-                port.port_type, 
-                history.clone()
-            );
-            histories.push(history);
+        let selected_ports: Vec<PortInfo> = initial_ports
+            .into_iter()
+            .filter(|p| selection.matches(p) && type_filter.matches(p.port_type))
+            .collect();
+
+        if selected_ports.is_empty() && !waiting {
+            anyhow::bail!("No valid interfaces selected to monitor.");
         }
 
-        let items_count = histories.len();
+        // 确认有端口可监控后再创建录制文件，避免启动失败时留下空文件
+        let recorder = settings.record.as_deref().map(Recorder::create).transpose()?;
+        config.recorder = recorder.as_ref().map(Recorder::sink);
 
-        Ok(Self {
+        let mut app = Self {
             should_quit: false,
             view_mode: ViewMode::Chart,
+            unit: settings.units,
+            show_utilization: false,
+            show_help: false,
+            notice: None,
             version,
-            histories,
+            histories: Vec::new(),
+            port_names: Vec::new(),
+            monitor_config: config,
+            selection,
+            type_filter,
+            metrics_port: settings.metrics_port,
+            fps: settings.fps,
+            theme: Theme::preset(settings.theme).with_overrides(&settings.colors),
+            theme_name: settings.theme,
+            colors: settings.colors,
+            alerts: settings.alerts,
+            monitor_queues: settings.monitor_queues,
+            include_virtual: settings.include_virtual,
+            monitor_threads: Vec::new(),
+            recorder,
+            histories_tx: watch::Sender::new(Vec::new()),
             // 初始化滚动状态
             vertical_scroll: 0,
-            scroll_state: ScrollbarState::new(items_count), 
-        })
+            scroll_state: ScrollbarState::new(0),
+            sort_mode: SortMode::Name,
+            display_order: Vec::new(),
+            selected: 0,
+            focused: None,
+            grid_area: Rect::default(),
+        };
+        for port in selected_ports {
+            app.add_port(port);
+        }
+        app.publish_histories();
+        app.refresh_display_order();
+        // stderr 随即被备用屏幕盖住，TUI 中同时在状态栏提示
+        if !warnings.is_empty() {
+            app.notify(warnings.join(" "));
+        }
+
+        Ok(app)
+    }
+
+    /// 为端口创建 `PortHistory` 并启动对应的监控线程，追加到列表末尾
+    fn add_port(&mut self, port: PortInfo) {
+        let config = &self.monitor_config;
+        let mut port_history = PortHistory::new(port.name.clone(), port.port_type, config.history_points);
+        port_history.link_rate_bps = port.link_rate_bps;
+        port_history.link_layer = port.link_layer;
+        let history = Arc::new(RwLock::new(port_history));
+        self.monitor_threads.push(spawn_error_monitor(
+            port.device_path_part.clone(),
+            port.port_num_part.clone(),
+            port.port_type,
+            config.clone(),
+            history.clone()
+        ));
+        if self.monitor_queues && port.port_type == PortType::Ethernet {
+            self.monitor_threads.push(spawn_queue_monitor(port.device_path_part.clone(), config.clone(), history.clone()));
+        }
+        self.monitor_threads.push(spawn_chart_monitor(
+            port.device_path_part, 
+            port.port_num_part, 
+            port.port_type, 
+            config.clone(),
+            history.clone()
+        ));
+        self.histories.push(history);
+        self.port_names.push(port.name);
+    }
+
+    fn publish_histories(&mut self) {
+        self.scroll_state = self.scroll_state.content_length(self.histories.len());
+        self.histories_tx.send_replace(self.histories.clone());
+    }
+
+    fn is_monitored(&self, name: &str) -> bool {
+        self.port_names.iter().any(|n| n == name)
+    }
+
+    /// `--interfaces` 点名但尚未开始监控的端口
+    pub fn waiting_for(&self) -> Vec<&str> {
+        match &self.selection {
+            PortSelection::All => Vec::new(),
+            PortSelection::Named(names) => names.iter().filter(|n| !self.is_monitored(n)).map(String::as_str).collect(),
+        }
+    }
+
+    /// 重新扫描 sysfs，为新出现的端口启动监控
+    ///
+    /// 新端口追加到 `histories` 末尾，已有下标 (光标、详情视图) 不变；
+    /// 在网格中的位置由 `display_order` 按排序方式决定。
+    /// 已消失的端口由其监控线程自行标记为 stale，这里不做删除。
+    pub async fn rediscover(&mut self) -> Result<usize> {
+        if let PortSelection::Named(names) = &self.selection
+            && names.iter().all(|n| self.is_monitored(n)) {
+            return Ok(0);
+        }
+
+        let ports = discover_ports(&self.monitor_config.sysfs_root, self.include_virtual).await?;
+        let new_ports: Vec<PortInfo> = ports
+            .into_iter()
+            .filter(|p| {
+                self.selection.matches(p) && self.type_filter.matches(p.port_type) && !self.is_monitored(&p.name)
+            })
+            .collect();
+
+        let added = new_ports.len();
+        if added > 0 {
+            for port in new_ports {
+                self.add_port(port);
+            }
+            self.publish_histories();
+        }
+        Ok(added)
+    }
+
+    /// 按配置启动 Prometheus 服务；未配置端口时返回 `None`
+    pub async fn spawn_metrics_server(&self) -> Result<Option<tokio::task::JoinHandle<()>>> {
+        match self.metrics_port {
+            Some(port) => Ok(Some(metrics::spawn_server(port, self.histories_tx.subscribe()).await?)),
+            None => Ok(None),
+        }
     }
 
     pub async fn run(&mut self, tui: &mut Tui) -> Result<()> {
+        let metrics_server = self.spawn_metrics_server().await?;
         let mut event_stream = EventStream::new();
-        let mut ui_interval = tokio::time::interval(Duration::from_millis(100));
+        // 定时重绘；按键与鼠标事件由 select! 立即唤醒循环并重绘，不必等下一帧
+        let mut ui_interval = tokio::time::interval(Duration::from_secs(1) / self.fps);
+        let mut rediscover_interval = tokio::time::interval(REDISCOVER_INTERVAL);
+        rediscover_interval.tick().await; // 首次 tick 立即触发，启动时已扫描过
+        // 信号监听只创建一次：每轮重建会丢失两次 select 之间到达的 SIGINT
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
 
         while !self.should_quit {
             tui.draw(|f| ui::render(self, f))?;
 
             tokio::select! {
                 _ = ui_interval.tick() => {},
+                _ = rediscover_interval.tick() => {
+                    // 扫描失败 (如 sysfs 暂时不可读) 不影响已有端口，下个周期再试
+                    let _ = self.rediscover().await;
+                },
                 Some(Ok(event)) = event_stream.next() => {
-                    if let Event::Key(key) = event {
-                        handle_key_event(key, self)?;
+                    match event {
+                        Event::Key(key) => handle_key_event(key, self)?,
+                        Event::Mouse(mouse) => handle_mouse_event(mouse, self)?,
+                        _ => {}
                     }
                 },
-                _ = tokio::signal::ctrl_c() => {
+                _ = &mut ctrl_c => {
                     self.quit();
                 },
             }
         }
+
+        if let Some(server) = metrics_server {
+            server.abort();
+        }
         Ok(())
     }
 
     pub fn toggle_view_mode(&mut self) {
         self.view_mode = match self.view_mode {
-            ViewMode::Table => ViewMode::Chart,
-            ViewMode::Chart => ViewMode::Table,
+            ViewMode::Chart => ViewMode::Packets,
+            ViewMode::Packets => ViewMode::Errors,
+            ViewMode::Errors if self.monitor_queues => ViewMode::Queues,
+            ViewMode::Errors | ViewMode::Queues => ViewMode::Table,
+            ViewMode::Table => ViewMode::Sparkline,
+            ViewMode::Sparkline => ViewMode::Chart,
+        };
+    }
+
+    pub fn toggle_unit(&mut self) {
+        self.unit = self.unit.toggle();
+    }
+
+    /// 循环切换内置主题，配置文件中的颜色覆盖保持生效
+    pub fn cycle_theme(&mut self) {
+        self.theme_name = self.theme_name.next();
+        self.theme = Theme::preset(self.theme_name).with_overrides(&self.colors);
+    }
+
+    pub fn toggle_utilization(&mut self) {
+        self.show_utilization = !self.show_utilization;
+    }
+
+    /// 在状态栏中显示一条提示
+    pub fn notify(&mut self, message: String) {
+        self.notice = Some((message, Instant::now()));
+    }
+
+    /// 尚未过期的状态栏提示
+    pub fn active_notice(&self) -> Option<&str> {
+        self.notice
+            .as_ref()
+            .filter(|(_, at)| at.elapsed() < NOTICE_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// 当前状态的纯文本快照：显示设置，以及每个端口的最新速率与窗口统计
+    ///
+    /// 端口按网格的显示顺序排列。只用 `try_read`，正在被监控线程写入的端口
+    /// 标记为 busy 而不是等待锁，名称取自不加锁的 `port_names`。
+    pub fn snapshot(&self) -> String {
+        let mut out = String::new();
+        let config = &self.monitor_config;
+        let _ = writeln!(
+            out,
+            "RDMA Monitor v{} snapshot {}",
+            self.version,
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+        );
+        let _ = writeln!(
+            out,
+            "View: {} | Units: {} | Aggregation: {} | Sort: {} | Window: {} points x {}ms{}",
+            ui::view_mode_label(self),
+            self.unit.label(),
+            self.aggregation().label(),
+            self.sort_mode.label(),
+            config.history_points,
+            config.commit_interval.as_millis(),
+            if self.is_paused() { " | PAUSED" } else { "" }
+        );
+
+        for &index in &self.display_order {
+            out.push('\n');
+            match self.histories.get(index).map(|lock| lock.try_read()) {
+                Some(Ok(history)) => out.push_str(&format_port_snapshot(&history, self.unit)),
+                Some(Err(_)) => {
+                    let name = self.port_names.get(index).map(String::as_str).unwrap_or("?");
+                    let _ = writeln!(out, "{} (port busy, skipped)", name);
+                }
+                None => {}
+            }
+        }
+        out
+    }
+
+    pub fn toggle_help(&mut self) {
+        self.show_help = !self.show_help;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.monitor_config.control.is_paused()
+    }
+
+    /// 暂停/恢复所有监控线程的数据提交，UI 继续渲染暂停前的数据
+    pub fn toggle_pause(&mut self) {
+        let control = &self.monitor_config.control;
+        control.set_paused(!control.is_paused());
+    }
+
+    pub fn aggregation(&self) -> Aggregation {
+        self.monitor_config.control.aggregation()
+    }
+
+    /// 切换窗口聚合策略，从下一次提交起生效，已有历史点不受影响
+    pub fn cycle_aggregation(&mut self) {
+        let control = &self.monitor_config.control;
+        control.set_aggregation(control.aggregation().next());
+    }
+
+    /// 所有端口最新 RX/TX 速率之和 (Bytes/s)
+    ///
+    /// 各端口的监控线程独立提交，最新点的时刻彼此相差最多一个提交间隔，
+    /// 因此这里是"各自最近一点"之和，只是同一时刻总吞吐的近似值。断线端口不计入。
+    pub fn aggregate_latest(&self) -> (f64, f64) {
+        self.histories
+            .iter()
+            .filter_map(|h| h.read().ok())
+            .fold((0.0, 0.0), |(rx, tx), h| {
+                let (last_rx, last_tx) = latest_rates(&h);
+                (rx + last_rx, tx + last_tx)
+            })
+    }
+
+    /// 按最新一个点判断端口是否越过告警阈值
+    ///
+    /// 阈值与速率都以 Bytes/s 保存，显示单位切换不影响判断；
+    /// 恰好等于阈值不算越界。断线端口与尚无数据的端口不告警。
+    pub fn alert_state(&self, history: &PortHistory) -> AlertLevel {
+        let (Some(&(_, rx)), Some(&(_, tx))) = (history.rx_data.back(), history.tx_data.back()) else {
+            return AlertLevel::Normal;
         };
+        if history.stale {
+            return AlertLevel::Normal;
+        }
+        let thresholds = self.alerts.for_port(&history.name);
+        let peak = rx.max(tx);
+        if thresholds.high.is_some_and(|high| peak > high) {
+            AlertLevel::High
+        } else if thresholds.low.is_some_and(|low| peak < low) {
+            AlertLevel::Low
+        } else {
+            AlertLevel::Normal
+        }
+    }
+
+    /// 当前处于告警状态的端口数
+    pub fn alert_count(&self) -> usize {
+        self.histories
+            .iter()
+            .filter_map(|h| h.read().ok())
+            .filter(|h| self.alert_state(h) != AlertLevel::Normal)
+            .count()
+    }
+
+    /// 清空所有端口的历史缓冲，各监控线程在下一次提交时重置时间轴
+    pub fn clear_histories(&mut self) {
+        for history in &self.histories {
+            if let Ok(mut h) = history.write() {
+                h.clear();
+            }
+        }
+    }
+
+    /// 停止 CSV 录制并返回统计；未启用 `--record` 时返回 `None`
+    pub fn finish_recording(&mut self) -> Result<Option<(std::path::PathBuf, RecordSummary)>> {
+        let Some(recorder) = self.recorder.take() else { return Ok(None) };
+        let path = recorder.path().to_path_buf();
+        Ok(Some((path, recorder.finish()?)))
     }
 
     pub fn quit(&mut self) {
         self.should_quit = true;
+        self.monitor_config.control.request_shutdown();
+    }
+
+    /// 通知监控线程退出并在 `timeout` 内等待它们结束，返回超时仍未退出的线程数
+    ///
+    /// 未结束的线程不再等待，进程退出时由系统回收。
+    pub fn shutdown_monitors(&mut self, timeout: Duration) -> usize {
+        self.monitor_config.control.request_shutdown();
+        let deadline = std::time::Instant::now() + timeout;
+        while self.monitor_threads.iter().any(|h| !h.is_finished()) && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let (finished, running): (Vec<_>, Vec<_>) = self.monitor_threads.drain(..).partition(|h| h.is_finished());
+        for handle in finished {
+            let _ = handle.join();
+        }
+        running.len()
     }
 
     // --- 新增：滚动控制逻辑 ---
+    // 光标移动，视口在渲染时由 scroll_to_selected 跟随
+    // 上下移动按显示顺序，而不是 histories 下标
     pub fn on_up(&mut self) {
-        if self.vertical_scroll > 0 {
-            self.vertical_scroll = self.vertical_scroll.saturating_sub(1);
-            self.scroll_state = self.scroll_state.position(self.vertical_scroll);
+        let position = self.selected_position();
+        if position > 0 {
+            self.selected = self.display_order[position - 1];
         }
     }
 
     pub fn on_down(&mut self) {
-        if self.vertical_scroll < self.histories.len().saturating_sub(1) {
-            self.vertical_scroll = self.vertical_scroll.saturating_add(1);
-            self.scroll_state = self.scroll_state.position(self.vertical_scroll);
+        let position = self.selected_position();
+        if position + 1 < self.display_order.len() {
+            self.selected = self.display_order[position + 1];
+        }
+    }
+
+    /// 光标端口在网格中的位置
+    fn selected_position(&self) -> usize {
+        self.display_order.iter().position(|&i| i == self.selected).unwrap_or(0)
+    }
+
+    pub fn cycle_sort(&mut self) {
+        self.sort_mode = self.sort_mode.next();
+        self.refresh_display_order();
+    }
+
+    /// 按 `sort_mode` 重新计算 `display_order`
+    ///
+    /// 名称模式下所有端口的 key 都为 0，只按名称排序。
+    pub fn refresh_display_order(&mut self) {
+        let mode = self.sort_mode;
+        let mut keys: Vec<(usize, f64, String)> = self
+            .histories
+            .iter()
+            .enumerate()
+            .map(|(i, lock)| match lock.read() {
+                Ok(h) => (i, mode.key(&h), h.name.clone()),
+                Err(_) => (i, 0.0, String::new()),
+            })
+            .collect();
+        keys.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.2.cmp(&b.2)));
+        self.display_order = keys.into_iter().map(|(i, _, _)| i).collect();
+    }
+
+    /// 调整滚动偏移，保证光标位于可见的 `items_per_screen` 个面板内
+    pub fn scroll_to_selected(&mut self, items_per_screen: usize) {
+        let visible = items_per_screen.max(1);
+        let position = self.selected_position();
+        if position < self.vertical_scroll {
+            self.vertical_scroll = position;
+        } else if position >= self.vertical_scroll + visible {
+            self.vertical_scroll = position + 1 - visible;
+        }
+        self.scroll_state = self.scroll_state.position(self.vertical_scroll);
+    }
+
+    /// 鼠标点击选中端口；点击已选中的端口进入详情视图
+    pub fn select_port(&mut self, index: usize) {
+        if index >= self.histories.len() {
+            return;
+        }
+        if self.selected == index {
+            self.focused = Some(index);
+        } else {
+            self.selected = index;
+        }
+    }
+
+    /// 进入/退出选中端口的全屏详情视图
+    pub fn toggle_focus(&mut self) {
+        self.focused = match self.focused {
+            Some(_) => None,
+            None if self.selected < self.histories.len() => Some(self.selected),
+            None => None,
+        };
+    }
+
+    /// 退出详情视图，回到网格
+    pub fn close_focus(&mut self) {
+        self.focused = None;
+    }
+
+    /// 端口列表变化后修正越界的光标与详情下标
+    pub fn clamp_selection(&mut self) {
+        let len = self.histories.len();
+        self.selected = self.selected.min(len.saturating_sub(1));
+        if self.focused.is_some_and(|i| i >= len) {
+            self.focused = None;
+        }
+    }
+}
+
+/// 单个端口在文本快照中的几行；尚无数据的端口只输出标题与提示
+pub fn format_port_snapshot(history: &PortHistory, unit: SpeedUnit) -> String {
+    let mut out = String::new();
+    let stale = if history.stale { ui::STALE_SUFFIX } else { "" };
+    let _ = writeln!(out, "{} {}{}", ui::port_label(history).trim_end(), history.name, stale);
+
+    if history.rx_data.is_empty() {
+        out.push_str("  (no data yet)\n");
+        return out;
+    }
+    let stats = history.stats();
+    for (direction, series) in [("RX", &stats.rx), ("TX", &stats.tx)] {
+        let _ = writeln!(
+            out,
+            "  {}  current {}  avg {}  min {}  max {}",
+            direction,
+            ui::format_speed(series.current, unit),
+            ui::format_speed(series.avg, unit),
+            ui::format_speed(series.min, unit),
+            ui::format_speed(series.max, unit)
+        );
+    }
+    let _ = writeln!(
+        out,
+        "  Total  RX {}  TX {}",
+        ui::format_bytes(history.total_rx_bytes),
+        ui::format_bytes(history.total_tx_bytes)
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{fake_history, test_app, test_config, TempTree};
+
+    /// 只含一个以太网端口的 App，监控线程已停止
+    async fn app_with(histories: Vec<Arc<RwLock<PortHistory>>>) -> App {
+        let tree = TempTree::new("app");
+        tree.eth_port("eth0", true);
+        let mut app = test_app(&tree, &["--all"]).await;
+        app.port_names = histories.iter().map(|h| h.read().unwrap().name.clone()).collect();
+        app.histories = histories;
+        app.refresh_display_order();
+        app
+    }
+
+    #[tokio::test]
+    async fn aggregate_latest_sums_last_points() {
+        let app = app_with(vec![
+            fake_history("eth0", PortType::Ethernet, &[(5.0, 7.0), (100.0, 10.0)]),
+            fake_history("eth1", PortType::Ethernet, &[]),
+            fake_history("mlx5_0-1", PortType::Rdma, &[(900.0, 1.0), (50.0, 20.0)]),
+        ]).await;
+        assert_eq!(app.aggregate_latest(), (150.0, 30.0));
+    }
+
+    #[tokio::test]
+    async fn stale_ports_count_as_zero_for_totals_and_sorting() {
+        let app = app_with(vec![
+            fake_history("eth0", PortType::Ethernet, &[(100.0, 10.0)]),
+            fake_history("eth1", PortType::Ethernet, &[(500.0, 500.0)]),
+        ]).await;
+        app.histories[1].write().unwrap().stale = true;
+        assert_eq!(app.aggregate_latest(), (100.0, 10.0));
+        assert_eq!(SortMode::Total.key(&app.histories[1].read().unwrap()), 0.0);
+        assert_eq!(SortMode::Rx.key(&app.histories[0].read().unwrap()), 100.0);
+    }
+
+    fn port_names(app: &App) -> Vec<String> {
+        app.histories.iter().map(|h| h.read().unwrap().name.clone()).collect()
+    }
+
+    #[tokio::test]
+    async fn named_ports_that_are_absent_are_waited_for() {
+        let tree = TempTree::new("waiting");
+        tree.eth_port("eth0", true);
+        let mut app = test_app(&tree, &["-i", "eth5"]).await;
+        assert!(app.histories.is_empty());
+        assert_eq!(app.waiting_for(), vec!["eth5"]);
+        assert_eq!(app.active_notice(), Some("Waiting for eth5 to appear."));
+
+        // 设备出现后由重新扫描补上，且不会重复添加
+        tree.eth_port("eth5", true);
+        assert_eq!(app.rediscover().await.unwrap(), 1);
+        assert_eq!(app.rediscover().await.unwrap(), 0);
+        assert_eq!(port_names(&app), vec!["eth5"]);
+        assert!(app.waiting_for().is_empty());
+    }
+
+    #[tokio::test]
+    async fn named_mode_starts_without_any_devices() {
+        let tree = TempTree::new("waiting");
+        let app = test_app(&tree, &["-i", "mlx5_0-1", "-i", "eth0"]).await;
+        assert_eq!(app.waiting_for(), vec!["mlx5_0-1", "eth0"]);
+        // --all 没有任何端口时仍然报错
+        let err = App::try_new(test_config(&tree, &["--all"])).await.err().unwrap();
+        assert!(err.to_string().contains("No physical"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn rediscover_appends_new_ports_in_all_mode() {
+        let tree = TempTree::new("rediscover");
+        tree.eth_port("eth1", true);
+        let mut app = test_app(&tree, &["--all"]).await;
+        tree.eth_port("eth0", true);
+        assert_eq!(app.rediscover().await.unwrap(), 1);
+        // 新端口追加在末尾，已有面板位置不变
+        assert_eq!(port_names(&app), vec!["eth1", "eth0"]);
+        assert_eq!(app.histories_tx.borrow().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn cursor_stays_in_range_and_scrolls_into_view() {
+        let tree = TempTree::new("cursor");
+        for dev in ["eth0", "eth1", "eth2", "eth3"] {
+            tree.eth_port(dev, true);
+        }
+        let mut app = test_app(&tree, &["--all"]).await;
+        app.on_up();
+        assert_eq!(app.selected, 0);
+        for _ in 0..10 {
+            app.on_down();
+        }
+        assert_eq!(app.selected, 3);
+
+        // 每屏两个面板：光标在第 4 个时从第 3 个开始显示
+        app.scroll_to_selected(2);
+        assert_eq!(app.vertical_scroll, 2);
+        app.selected = 0;
+        app.scroll_to_selected(2);
+        assert_eq!(app.vertical_scroll, 0);
+    }
+
+    #[tokio::test]
+    async fn focus_follows_the_cursor_and_is_cleared_when_out_of_range() {
+        let tree = TempTree::new("focus");
+        tree.eth_port("eth0", true);
+        tree.eth_port("eth1", true);
+        let mut app = test_app(&tree, &["--all"]).await;
+        app.on_down();
+        app.toggle_focus();
+        assert_eq!(app.focused, Some(1));
+        app.toggle_focus();
+        assert_eq!(app.focused, None);
+
+        app.toggle_focus();
+        app.histories.truncate(1);
+        app.clamp_selection();
+        assert_eq!((app.selected, app.focused), (0, None));
+    }
+
+    fn alerts() -> Alerts {
+        let mut ports = std::collections::HashMap::new();
+        ports.insert("eth1".to_string(), AlertThresholds { high: Some(1000.0), low: None });
+        ports.insert("eth2".to_string(), AlertThresholds { high: None, low: Some(1.0) });
+        Alerts { default: AlertThresholds { high: Some(100.0), low: Some(10.0) }, ports }
+    }
+
+    #[tokio::test]
+    async fn alert_state_boundaries() {
+        let mut app = app_with(Vec::new()).await;
+        app.alerts = alerts();
+        let level = |app: &App, points: &[(f64, f64)]| {
+            let history = fake_history("eth0", PortType::Ethernet, points);
+            let h = history.read().unwrap();
+            app.alert_state(&h)
+        };
+        // 恰好等于阈值不算越界
+        assert_eq!(level(&app, &[(100.0, 50.0)]), AlertLevel::Normal);
+        assert_eq!(level(&app, &[(10.0, 10.0)]), AlertLevel::Normal);
+        assert_eq!(level(&app, &[(50.0, 100.5)]), AlertLevel::High);
+        assert_eq!(level(&app, &[(5.0, 9.0)]), AlertLevel::Low);
+        // 只有一个方向低于下限时不算流量中断
+        assert_eq!(level(&app, &[(5.0, 50.0)]), AlertLevel::Normal);
+        // 只看最新一个点
+        assert_eq!(level(&app, &[(500.0, 500.0), (50.0, 50.0)]), AlertLevel::Normal);
+        assert_eq!(level(&app, &[]), AlertLevel::Normal);
+
+        let history = fake_history("eth0", PortType::Ethernet, &[(500.0, 500.0)]);
+        history.write().unwrap().stale = true;
+        assert_eq!(app.alert_state(&history.read().unwrap()), AlertLevel::Normal);
+
+        app.alerts = Alerts::default();
+        assert_eq!(level(&app, &[(1e12, 0.0)]), AlertLevel::Normal);
+    }
+
+    #[test]
+    fn port_thresholds_override_defaults_field_by_field() {
+        let alerts = alerts();
+        assert_eq!(alerts.for_port("eth1"), AlertThresholds { high: Some(1000.0), low: Some(10.0) });
+        assert_eq!(alerts.for_port("eth2"), AlertThresholds { high: Some(100.0), low: Some(1.0) });
+        assert_eq!(alerts.for_port("eth9"), alerts.default);
+    }
+
+    #[tokio::test]
+    async fn alert_state_uses_port_override() {
+        let mut app = app_with(Vec::new()).await;
+        app.alerts = alerts();
+        let busy = fake_history("eth1", PortType::Ethernet, &[(500.0, 5.0)]);
+        let quiet = fake_history("eth2", PortType::Ethernet, &[(5.0, 5.0)]);
+        assert_eq!(app.alert_state(&busy.read().unwrap()), AlertLevel::Normal);
+        assert_eq!(app.alert_state(&quiet.read().unwrap()), AlertLevel::Normal);
+        busy.write().unwrap().push_point(1.0, 0, 1001.0, 0.0);
+        assert_eq!(app.alert_state(&busy.read().unwrap()), AlertLevel::High);
+    }
+
+    #[tokio::test]
+    async fn shutdown_monitors_joins_every_thread() {
+        let tree = TempTree::new("shutdown");
+        tree.eth_port("eth0", true);
+        tree.ib_port("mlx5_0", "1");
+        let mut app = App::try_new(test_config(&tree, &["--all"])).await.unwrap();
+        // 每个端口一个吞吐线程与一个错误计数线程
+        assert_eq!(app.monitor_threads.len(), 4);
+
+        let started = Instant::now();
+        assert_eq!(app.shutdown_monitors(SHUTDOWN_TIMEOUT), 0);
+        assert!(started.elapsed() < SHUTDOWN_TIMEOUT);
+        assert!(app.monitor_threads.is_empty());
+    }
+
+    /// 一个 RDMA 端口与一个以太网端口的 sysfs 树
+    fn mixed_tree() -> TempTree {
+        let tree = TempTree::new("filter");
+        tree.ib_port("mlx5_0", "1");
+        tree.eth_port("eth0", true);
+        tree
+    }
+
+    #[tokio::test]
+    async fn type_filter_selects_ports_by_type() {
+        let tree = mixed_tree();
+        for (filter, expected) in [
+            ("rdma", vec!["mlx5_0-1"]),
+            ("ethernet", vec!["eth0"]),
+            ("both", vec!["eth0", "mlx5_0-1"]),
+        ] {
+            let app = test_app(&tree, &["--all", "--type", filter]).await;
+            assert_eq!(port_names(&app), expected, "--type {}", filter);
+            assert_eq!(app.active_notice(), None);
         }
+        assert_eq!(port_names(&test_app(&tree, &["--all"]).await), vec!["eth0", "mlx5_0-1"]);
+    }
+
+    #[tokio::test]
+    async fn named_port_excluded_by_type_is_reported() {
+        let tree = mixed_tree();
+        let app = test_app(&tree, &["-i", "mlx5_0-1", "-i", "eth0", "--type", "rdma"]).await;
+        assert_eq!(port_names(&app), vec!["mlx5_0-1"]);
+        assert_eq!(app.active_notice(), Some("Warning: eth0 (ethernet) is excluded by --type rdma."));
+
+        // 点名的端口全部被排除时无法启动
+        let err = App::try_new(test_config(&tree, &["-i", "eth0", "--type", "rdma"])).await.err().unwrap();
+        assert!(err.to_string().contains("No valid interfaces"), "{}", err);
     }
-}
\ No newline at end of file
+
+    fn display_names(app: &App) -> Vec<String> {
+        app.display_order.iter().map(|&i| app.histories[i].read().unwrap().name.clone()).collect()
+    }
+
+    #[tokio::test]
+    async fn name_sort_orders_rediscovered_ports_by_name() {
+        // 模拟重新扫描后追加在末尾的端口
+        let mut app = app_with(vec![
+            fake_history("eth1", PortType::Ethernet, &[(1.0, 9.0)]),
+            fake_history("mlx5_0-1", PortType::Rdma, &[(5.0, 1.0)]),
+            fake_history("eth0", PortType::Ethernet, &[(5.0, 2.0)]),
+        ]).await;
+        assert_eq!(app.sort_mode, SortMode::Name);
+        assert_eq!(display_names(&app), vec!["eth0", "eth1", "mlx5_0-1"]);
+
+        // 速率相同时按名称
+        app.cycle_sort();
+        assert_eq!(app.sort_mode, SortMode::Rx);
+        assert_eq!(display_names(&app), vec!["eth0", "mlx5_0-1", "eth1"]);
+        app.cycle_sort();
+        assert_eq!(display_names(&app), vec!["eth1", "eth0", "mlx5_0-1"]);
+    }
+
+    #[test]
+    fn port_snapshot_without_data() {
+        let history = fake_history("mlx5_0-1", PortType::Rdma, &[]);
+        history.write().unwrap().stale = true;
+        assert_eq!(
+            format_port_snapshot(&history.read().unwrap(), SpeedUnit::Bytes),
+            "[RDMA] mlx5_0-1 [DISCONNECTED]\n  (no data yet)\n"
+        );
+    }
+
+    #[test]
+    fn port_snapshot_with_stats_and_totals() {
+        let history = fake_history("eth0", PortType::Ethernet, &[(1024.0, 10.0), (3072.0, 30.0)]);
+        history.write().unwrap().total_rx_bytes = 2048;
+        assert_eq!(
+            format_port_snapshot(&history.read().unwrap(), SpeedUnit::Bytes),
+            "[ETH] eth0\n\
+             \x20 RX  current 3.0 KB/s  avg 2.0 KB/s  min 1.0 KB/s  max 3.0 KB/s\n\
+             \x20 TX  current 30 B/s  avg 20 B/s  min 10 B/s  max 30 B/s\n\
+             \x20 Total  RX 2.00 KiB  TX 0 B\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn snapshot_lists_ports_in_display_order_and_names_busy_ones() {
+        let mut app = app_with(vec![
+            fake_history("eth1", PortType::Ethernet, &[(1.0, 1.0)]),
+            fake_history("eth0", PortType::Ethernet, &[]),
+        ]).await;
+        app.toggle_pause();
+        let text = app.snapshot();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with(&format!("RDMA Monitor v{} snapshot ", app.version)));
+        assert_eq!(lines[1], "View: Oscilloscope Mode (1ms Precision) | Units: Bytes/s | Aggregation: Peak | Sort: Name | Window: 600 points x 10ms | PAUSED");
+        assert_eq!(&lines[2..5], &["", "[ETH] eth0", "  (no data yet)"]);
+        assert_eq!(&lines[5..7], &["", "[ETH] eth1"]);
+
+        // 监控线程正持有写锁的端口被跳过，但仍注明是哪个端口
+        let _busy = app.histories[1].write().unwrap();
+        let text = app.snapshot();
+        assert!(text.contains("\n\neth0 (port busy, skipped)\n"), "{}", text);
+        assert!(text.contains("[ETH] eth1"));
+    }
+}
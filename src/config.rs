@@ -0,0 +1,368 @@
+use crate::app::{AlertThresholds, Alerts, PortSelection, DEFAULT_FPS, MAX_FPS, MIN_FPS};
+use crate::data::PortTypeFilter;
+use crate::summary::DEFAULT_ONCE_SECS;
+use crate::monitor::{Aggregation, DEFAULT_COMMIT_MS, DEFAULT_HISTORY_POINTS, DEFAULT_SAMPLE_US};
+use crate::theme::ThemeName;
+use crate::ui::{parse_speed, SpeedUnit};
+use crate::{Args, OutputFormat};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use ratatui::style::Color;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 配置文件中可设置的项，全部可选；未设置的项回落到命令行默认值
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FileConfig {
+    pub all: Option<bool>,
+    pub interfaces: Option<Vec<String>>,
+    pub monitor_queues: Option<bool>,
+    pub include_virtual: Option<bool>,
+    /// 与 `--type` 取值相同 (`rdma` / `ethernet` / `both`)
+    #[serde(rename = "type")]
+    pub port_type: Option<String>,
+    pub sample_interval_us: Option<u64>,
+    pub commit_interval_ms: Option<u64>,
+    pub history_points: Option<usize>,
+    pub fps: Option<u32>,
+    /// 与 `--units` 取值相同 (`bytes` / `bits`)
+    pub units: Option<String>,
+    /// 与 `--aggregate` 取值相同 (`peak` / `avg` / `last`)
+    pub aggregate: Option<String>,
+    /// 与 `--theme` 取值相同 (`dark` / `light` / `mono`)
+    pub theme: Option<String>,
+    /// 叠加在主题之上的颜色
+    pub colors: ColorConfig,
+    pub alerts: AlertConfig,
+}
+
+/// 速率取值：纯数字按 `units` 解释，字符串可带单位，如 `"80Gb/s"`、`"500MB/s"`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum RateValue {
+    Number(f64),
+    Text(String),
+}
+
+/// 一组阈值 (`[alerts]` 或 `[alerts.ports."<iface>"]`)
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThresholdConfig {
+    pub high: Option<RateValue>,
+    pub low: Option<RateValue>,
+}
+
+/// `[alerts]` 小节：全局阈值，以及按端口名覆盖的阈值
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertConfig {
+    pub high: Option<RateValue>,
+    pub low: Option<RateValue>,
+    pub ports: std::collections::HashMap<String, ThresholdConfig>,
+}
+
+/// `[colors]` 小节，取值为颜色名 (`magenta`)、`#rrggbb` 或 256 色下标
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorConfig {
+    pub rdma: Option<String>,
+    pub ethernet: Option<String>,
+    pub rx: Option<String>,
+    pub tx: Option<String>,
+}
+
+/// 解析后的颜色覆盖；`None` 表示使用内置配色
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ColorOverrides {
+    /// RDMA 端口的边框与标题色
+    pub rdma: Option<Color>,
+    /// 以太网端口的边框与标题色
+    pub ethernet: Option<Color>,
+    /// RX / TX 的曲线与文字颜色
+    pub rx: Option<Color>,
+    pub tx: Option<Color>,
+}
+
+impl FileConfig {
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// 读取配置文件
+    ///
+    /// 显式指定 (`--config`) 的文件必须存在；默认路径下没有文件时视为空配置。
+    pub fn load(explicit: Option<&Path>) -> Result<Self> {
+        let (path, required) = match explicit {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_config_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text)
+                .with_context(|| format!("Invalid config file {}", path.display())),
+            Err(e) if !required && e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read config file {}", path.display())),
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/rdma-dashboard/config.toml`，未设置时为 `~/.config/rdma-dashboard/config.toml`
+pub fn default_config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("rdma-dashboard").join("config.toml"))
+}
+
+/// 命令行、配置文件与内置默认值合并后的最终设置
+///
+/// 优先级：命令行 > 配置文件 > 内置默认值。
+#[derive(Debug)]
+pub struct Config {
+    pub selection: PortSelection,
+    pub port_type: PortTypeFilter,
+    pub monitor_queues: bool,
+    pub include_virtual: bool,
+    pub output: OutputFormat,
+    /// `--once` 的采样时长；为 None 时按 `output` 运行
+    pub once: Option<Duration>,
+    pub sysfs_root: PathBuf,
+    pub sample_interval_us: u64,
+    pub commit_interval_ms: u64,
+    pub history_points: usize,
+    pub fps: u32,
+    pub units: SpeedUnit,
+    pub aggregate: Aggregation,
+    pub theme: ThemeName,
+    pub record: Option<PathBuf>,
+    pub metrics_port: Option<u16>,
+    pub colors: ColorOverrides,
+    pub alerts: Alerts,
+}
+
+impl Config {
+    pub fn resolve(args: Args, file: FileConfig) -> Result<Self> {
+        // 命令行给出任一模式时整体覆盖文件中的模式，二者不做合并
+        let selection = if args.mode.all {
+            PortSelection::All
+        } else if let Some(names) = args.mode.interfaces {
+            PortSelection::Named(names)
+        } else if file.all == Some(true) {
+            PortSelection::All
+        } else if let Some(names) = file.interfaces.filter(|n| !n.is_empty()) {
+            PortSelection::Named(names)
+        } else {
+            anyhow::bail!("Specify --all or --interfaces (or set `all` / `interfaces` in the config file).");
+        };
+
+        let port_type = match args.port_type {
+            Some(port_type) => port_type,
+            None => parse_enum(file.port_type.as_deref(), "type")?.unwrap_or_default(),
+        };
+        let units = match args.units {
+            Some(units) => units,
+            None => parse_enum(file.units.as_deref(), "units")?.unwrap_or(SpeedUnit::Bytes),
+        };
+        let aggregate = match args.aggregate {
+            Some(aggregate) => aggregate,
+            None => parse_enum(file.aggregate.as_deref(), "aggregate")?.unwrap_or_default(),
+        };
+        // 显式指定的主题优先于 NO_COLOR
+        let theme = match args.theme {
+            Some(theme) => theme,
+            None => match parse_enum(file.theme.as_deref(), "theme")? {
+                Some(theme) => theme,
+                None => ThemeName::from_no_color(std::env::var_os("NO_COLOR").as_deref()),
+            },
+        };
+        // 阈值字符串依赖 units 解释，放在 units 确定之后
+        let alerts = Alerts {
+            default: AlertThresholds {
+                high: match args.alert_high.as_deref() {
+                    Some(text) => Some(parse_rate_arg(text, units, "--alert-high")?),
+                    None => parse_rate_value(file.alerts.high.as_ref(), units, "alerts.high")?,
+                },
+                low: match args.alert_low.as_deref() {
+                    Some(text) => Some(parse_rate_arg(text, units, "--alert-low")?),
+                    None => parse_rate_value(file.alerts.low.as_ref(), units, "alerts.low")?,
+                },
+            },
+            ports: file.alerts.ports
+                .iter()
+                .map(|(name, port)| {
+                    Ok((name.clone(), AlertThresholds {
+                        high: parse_rate_value(port.high.as_ref(), units, "alerts.ports.*.high")?,
+                        low: parse_rate_value(port.low.as_ref(), units, "alerts.ports.*.low")?,
+                    }))
+                })
+                .collect::<Result<_>>()?,
+        };
+
+        let commit_interval_ms = args.commit_interval_ms
+            .or(file.commit_interval_ms)
+            .unwrap_or(DEFAULT_COMMIT_MS);
+        let once = args.once.then(|| Duration::from_secs(args.duration.unwrap_or(DEFAULT_ONCE_SECS)));
+        let mut history_points = args.history_points
+            .or(file.history_points)
+            .unwrap_or(DEFAULT_HISTORY_POINTS);
+        // 汇总模式下缓冲区要覆盖整个采样时长，否则最小值与峰值只反映最后一个窗口
+        if let Some(duration) = once {
+            let needed = duration.as_millis() / u128::from(commit_interval_ms.max(1)) + 1;
+            history_points = history_points.max(needed as usize);
+        }
+
+        Ok(Self {
+            selection,
+            port_type,
+            monitor_queues: resolve_flag(args.monitor_queues, args.no_monitor_queues, file.monitor_queues),
+            include_virtual: resolve_flag(args.include_virtual, args.no_include_virtual, file.include_virtual),
+            output: args.output,
+            once,
+            sysfs_root: args.sysfs_root,
+            sample_interval_us: args.sample_interval_us
+                .or(file.sample_interval_us)
+                .unwrap_or(DEFAULT_SAMPLE_US),
+            commit_interval_ms,
+            history_points,
+            // 超出范围时取最近的边界，而不是报错
+            fps: args.fps
+                .or(file.fps)
+                .unwrap_or(DEFAULT_FPS)
+                .clamp(MIN_FPS, MAX_FPS),
+            units,
+            aggregate,
+            theme,
+            record: args.record,
+            metrics_port: args.metrics_port,
+            colors: file.colors.resolve()?,
+            alerts,
+        })
+    }
+}
+
+impl ColorConfig {
+    fn resolve(&self) -> Result<ColorOverrides> {
+        Ok(ColorOverrides {
+            rdma: parse_color(self.rdma.as_deref(), "colors.rdma")?,
+            ethernet: parse_color(self.ethernet.as_deref(), "colors.ethernet")?,
+            rx: parse_color(self.rx.as_deref(), "colors.rx")?,
+            tx: parse_color(self.tx.as_deref(), "colors.tx")?,
+        })
+    }
+}
+
+/// 开关类选项：`--x` / `--no-x` (二者以后出现的为准) 优先于文件，都未给出时取文件值，默认关闭
+fn resolve_flag(on: bool, off: bool, file: Option<bool>) -> bool {
+    if on || off {
+        on
+    } else {
+        file.unwrap_or(false)
+    }
+}
+
+/// 按 clap 的取值名解析枚举，保证配置文件与命令行接受同样的写法
+fn parse_enum<T: ValueEnum>(value: Option<&str>, key: &str) -> Result<Option<T>> {
+    value
+        .map(|v| T::from_str(v, true).map_err(|e| anyhow::anyhow!("Invalid `{}` in config file: {}", key, e)))
+        .transpose()
+}
+
+fn parse_rate_arg(text: &str, units: SpeedUnit, flag: &str) -> Result<f64> {
+    parse_speed(text, units).map_err(|e| anyhow::anyhow!("Invalid {} `{}`: {}", flag, text, e))
+}
+
+fn parse_rate_value(value: Option<&RateValue>, units: SpeedUnit, key: &str) -> Result<Option<f64>> {
+    let text = match value {
+        None => return Ok(None),
+        Some(RateValue::Number(n)) => n.to_string(),
+        Some(RateValue::Text(t)) => t.clone(),
+    };
+    parse_speed(&text, units)
+        .map(Some)
+        .map_err(|e| anyhow::anyhow!("Invalid `{}` `{}` in config file: {}", key, text, e))
+}
+
+fn parse_color(value: Option<&str>, key: &str) -> Result<Option<Color>> {
+    value
+        .map(|v| v.parse::<Color>().map_err(|_| anyhow::anyhow!("Invalid color `{}` for `{}` in config file", v, key)))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    const SAMPLE: &str = r#"
+        all = true
+        monitor_queues = true
+        include_virtual = true
+        type = "rdma"
+        history_points = 100
+        fps = 30
+        units = "bits"
+
+        [alerts]
+        high = "80Gb/s"
+    "#;
+
+    fn resolve(args: &[&str], file: &str) -> Config {
+        let argv = std::iter::once("rdma-dashboard-rust").chain(args.iter().copied());
+        Config::resolve(Args::parse_from(argv), FileConfig::parse(file).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn file_values_override_defaults() {
+        let config = resolve(&[], SAMPLE);
+        assert!(matches!(config.selection, PortSelection::All));
+        assert!(config.monitor_queues);
+        assert!(config.include_virtual);
+        assert_eq!(config.port_type, PortTypeFilter::Rdma);
+        assert_eq!(config.history_points, 100);
+        assert_eq!(config.fps, 30);
+        assert_eq!(config.units, SpeedUnit::Bits);
+        assert_eq!(config.alerts.default.high, Some(80e9 / 8.0));
+        // 文件中没有的项取内置默认值
+        assert_eq!(config.sample_interval_us, DEFAULT_SAMPLE_US);
+        assert_eq!(config.commit_interval_ms, DEFAULT_COMMIT_MS);
+        assert_eq!(config.aggregate, Aggregation::Peak);
+    }
+
+    #[test]
+    fn cli_flags_override_file_values() {
+        let config = resolve(
+            &["-i", "eth0", "--type", "both", "--history-points", "50", "--fps", "20",
+              "--units", "bytes", "--alert-high", "1GB/s", "--no-monitor-queues", "--no-include-virtual"],
+            SAMPLE,
+        );
+        assert!(matches!(&config.selection, PortSelection::Named(names) if names == &["eth0"]));
+        assert!(!config.monitor_queues);
+        assert!(!config.include_virtual);
+        assert_eq!(config.port_type, PortTypeFilter::Both);
+        assert_eq!(config.history_points, 50);
+        assert_eq!(config.fps, 20);
+        assert_eq!(config.units, SpeedUnit::Bytes);
+        assert_eq!(config.alerts.default.high, Some(1024.0 * 1024.0 * 1024.0));
+    }
+
+    #[test]
+    fn fps_is_clamped_to_the_supported_range() {
+        assert_eq!(resolve(&[], "all = true").fps, DEFAULT_FPS);
+        assert_eq!(resolve(&[], "all = true\nfps = 0").fps, MIN_FPS);
+        assert_eq!(resolve(&["--fps", "1000"], "all = true").fps, MAX_FPS);
+    }
+
+    #[test]
+    fn negated_flags_follow_the_last_occurrence() {
+        let empty = "all = true";
+        assert!(!resolve(&[], empty).monitor_queues);
+        assert!(resolve(&["-q"], empty).monitor_queues);
+        assert!(resolve(&["--no-monitor-queues", "-q"], "all = true\nmonitor_queues = false").monitor_queues);
+        assert!(!resolve(&["--include-virtual", "--no-include-virtual"], SAMPLE).include_virtual);
+    }
+}
@@ -1,9 +1,22 @@
 use anyhow::{Context, Result};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 
-const IB_SYSFS_PATH: &str = "/sys/class/infiniband";
-const NET_SYSFS_PATH: &str = "/sys/class/net";
+/// 默认的 sysfs 挂载点；容器中可通过 `--sysfs-root` 指向 bind-mount 的宿主 sysfs
+pub const DEFAULT_SYSFS_ROOT: &str = "/sys";
+
+const IB_SYSFS_PATH: &str = "class/infiniband";
+const NET_SYSFS_PATH: &str = "class/net";
+
+/// `<root>/class/infiniband`
+pub fn ib_sysfs_dir(sysfs_root: &Path) -> PathBuf {
+    sysfs_root.join(IB_SYSFS_PATH)
+}
+
+/// `<root>/class/net`
+pub fn net_sysfs_dir(sysfs_root: &Path) -> PathBuf {
+    sysfs_root.join(NET_SYSFS_PATH)
+}
 
 /// 端口类型枚举：用于区分是 RDMA 设备还是普通以太网设备
 /// 这将决定后续 monitor 读取哪个 sysfs 文件，以及 UI 显示什么颜色
@@ -13,6 +26,46 @@ pub enum PortType {
     Ethernet, // 标准物理以太网
 }
 
+impl PortType {
+    /// 机器可读的类型名，用于 JSON 等结构化输出
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PortType::Rdma => "rdma",
+            PortType::Ethernet => "ethernet",
+        }
+    }
+}
+
+/// `--type`：按端口类型过滤发现结果
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PortTypeFilter {
+    /// Only RDMA (InfiniBand / RoCE) ports.
+    Rdma,
+    /// Only physical Ethernet NICs.
+    Ethernet,
+    /// Both kinds of ports.
+    #[default]
+    Both,
+}
+
+impl PortTypeFilter {
+    pub fn matches(self, port_type: PortType) -> bool {
+        match self {
+            PortTypeFilter::Rdma => port_type == PortType::Rdma,
+            PortTypeFilter::Ethernet => port_type == PortType::Ethernet,
+            PortTypeFilter::Both => true,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PortTypeFilter::Rdma => "rdma",
+            PortTypeFilter::Ethernet => "ethernet",
+            PortTypeFilter::Both => "both",
+        }
+    }
+}
+
 /// 端口元数据结构体
 /// 仅用于发现阶段，不包含统计数据
 #[derive(Debug, Clone)]
@@ -32,6 +85,12 @@ pub struct PortInfo {
     /// RDMA: "1"
     /// Eth: "" (空字符串)
     pub port_num_part: String,
+
+    /// 链路速率 (bits/s)，未知或链路 down 时为 None
+    pub link_rate_bps: Option<f64>,
+
+    /// RDMA 端口的链路层，以太网端口或旧内核没有 `link_layer` 文件时为 None
+    pub link_layer: Option<LinkLayer>,
 }
 
 impl PortInfo {
@@ -41,21 +100,69 @@ impl PortInfo {
             port_type,
             device_path_part: dev,
             port_num_part: port,
+            link_rate_bps: None,
+            link_layer: None,
         }
     }
 }
 
+/// RDMA 端口的链路层：原生 InfiniBand，或跑在以太网上的 RoCE
+///
+/// 只是 `PortType::Rdma` 下的细分，计数器路径与采样方式完全相同。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkLayer {
+    InfiniBand,
+    Roce,
+}
+
+/// 解析 IB 端口的 `link_layer` 文件，内核写入 "InfiniBand" 或 "Ethernet"
+pub fn parse_link_layer(content: &str) -> Option<LinkLayer> {
+    match content.trim() {
+        "InfiniBand" => Some(LinkLayer::InfiniBand),
+        "Ethernet" => Some(LinkLayer::Roce),
+        _ => None,
+    }
+}
+
+/// 解析 IB 端口的 `rate` 文件，例如 "100 Gb/sec (4X EDR)" 或 "2.5 Gb/sec (1X SDR)"
+pub fn parse_ib_rate(content: &str) -> Option<f64> {
+    let mut parts = content.split_whitespace();
+    let value: f64 = parts.next()?.parse().ok()?;
+    let multiplier = match parts.next()? {
+        "Gb/sec" => 1e9,
+        "Mb/sec" => 1e6,
+        _ => return None,
+    };
+    let bps = value * multiplier;
+    (bps > 0.0).then_some(bps)
+}
+
+/// 解析以太网的 `speed` 文件 (单位 Mb/s)，链路 down 时内核返回 -1
+pub fn parse_eth_speed(content: &str) -> Option<f64> {
+    let mbps: i64 = content.trim().parse().ok()?;
+    (mbps > 0).then_some(mbps as f64 * 1e6)
+}
+
+/// 读取并解析速率文件；文件缺失或读取失败 (链路 down 时 speed 可能返回 EINVAL) 均视为未知
+async fn read_link_rate(path: &Path, parse: fn(&str) -> Option<f64>) -> Option<f64> {
+    fs::read_to_string(path).await.ok().and_then(|c| parse(&c))
+}
+
 /// 扫描系统中的所有物理网络端口 (RDMA + Ethernet)
 ///
-/// 参数 `_monitor_queues` 被忽略，因为硬核模式下不调用 ethtool 以保证 1ms 精度。
-pub async fn discover_ports(_monitor_queues: bool) -> Result<Vec<PortInfo>> {
+/// `sysfs_root` 通常为 `/sys`，测试或容器环境下可指向一棵模拟的目录树。
+/// `include_virtual` 为 true 时一并返回没有 `device` 链接的以太网接口 (bond、team、VLAN、网桥等)。
+/// 每队列监控 (`-q`) 不影响发现结果，由 `App` 为以太网端口额外启动慢速线程。
+pub async fn discover_ports(sysfs_root: &Path, include_virtual: bool) -> Result<Vec<PortInfo>> {
     let mut ports = Vec::new();
+    let ib_dir = ib_sysfs_dir(sysfs_root);
+    let net_dir = net_sysfs_dir(sysfs_root);
 
     // ---------------------------------------------------------
-    // 1. 扫描 RDMA 设备 (/sys/class/infiniband)
+    // 1. 扫描 RDMA 设备 (<root>/class/infiniband)
     // ---------------------------------------------------------
-    if Path::new(IB_SYSFS_PATH).is_dir() {
-        let mut devices = fs::read_dir(IB_SYSFS_PATH).await
+    if ib_dir.is_dir() {
+        let mut devices = fs::read_dir(&ib_dir).await
             .context("Failed to read IB sysfs")?;
             
         while let Some(entry) = devices.next_entry().await? {
@@ -68,22 +175,27 @@ pub async fn discover_ports(_monitor_queues: bool) -> Result<Vec<PortInfo>> {
                     let port_num = p_entry.file_name().to_string_lossy().to_string();
                     let full_name = format!("{}-{}", dev_name, port_num);
                     
-                    ports.push(PortInfo::new(
+                    let mut info = PortInfo::new(
                         full_name,
                         PortType::Rdma,
                         dev_name.clone(),
                         port_num,
-                    ));
+                    );
+                    info.link_rate_bps = read_link_rate(&p_entry.path().join("rate"), parse_ib_rate).await;
+                    info.link_layer = fs::read_to_string(p_entry.path().join("link_layer")).await
+                        .ok()
+                        .and_then(|c| parse_link_layer(&c));
+                    ports.push(info);
                 }
             }
         }
     }
 
     // ---------------------------------------------------------
-    // 2. 扫描物理以太网设备 (/sys/class/net)
+    // 2. 扫描物理以太网设备 (<root>/class/net)
     // ---------------------------------------------------------
-    if Path::new(NET_SYSFS_PATH).is_dir() {
-        let mut devices = fs::read_dir(NET_SYSFS_PATH).await
+    if net_dir.is_dir() {
+        let mut devices = fs::read_dir(&net_dir).await
             .context("Failed to read Net sysfs")?;
             
         while let Some(entry) = devices.next_entry().await? {
@@ -92,18 +204,21 @@ pub async fn discover_ports(_monitor_queues: bool) -> Result<Vec<PortInfo>> {
             // 过滤回环接口
             if dev_name == "lo" { continue; }
             
-            // 关键过滤：只显示物理网卡
+            // 关键过滤：默认只显示物理网卡
             // 检查 /sys/class/net/<dev>/device 是否存在。
             // 虚拟网卡（如 docker0, veth, tun）通常没有 device 软链接。
-            // 注意：bonding 接口也没有 device，如果你想看 bond，可以去掉这个检查。
+            // bonding 接口也没有 device，需要看 bond 时用 --include-virtual 跳过这个检查；
+            // 其 statistics/ 下的 rx_bytes/tx_bytes 是成员口的汇总，采样方式与物理网卡相同。
             let device_link = entry.path().join("device");
-            if fs::metadata(&device_link).await.is_ok() {
-                ports.push(PortInfo::new(
+            if include_virtual || fs::metadata(&device_link).await.is_ok() {
+                let mut info = PortInfo::new(
                     dev_name.clone(),
                     PortType::Ethernet,
                     dev_name,
                     String::new(), // Ethernet 没有 ports/X 子目录结构
-                ));
+                );
+                info.link_rate_bps = read_link_rate(&entry.path().join("speed"), parse_eth_speed).await;
+                ports.push(info);
             }
         }
     }
@@ -112,4 +227,78 @@ pub async fn discover_ports(_monitor_queues: bool) -> Result<Vec<PortInfo>> {
     ports.sort_by(|a, b| a.name.cmp(&b.name));
     
     Ok(ports)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempTree;
+
+    /// 便于整体比较的 PortInfo 摘要
+    fn summarize(ports: &[PortInfo]) -> Vec<(String, PortType, String, String, Option<f64>)> {
+        ports
+            .iter()
+            .map(|p| (p.name.clone(), p.port_type, p.device_path_part.clone(), p.port_num_part.clone(), p.link_rate_bps))
+            .collect()
+    }
+
+    #[test]
+    fn link_rate_files_are_parsed_to_bits_per_second() {
+        assert_eq!(parse_ib_rate("100 Gb/sec (4X EDR)\n"), Some(100e9));
+        assert_eq!(parse_ib_rate("2.5 Gb/sec (1X SDR)"), Some(2.5e9));
+        assert_eq!(parse_ib_rate("0 Gb/sec"), None);
+        assert_eq!(parse_ib_rate("fast"), None);
+        assert_eq!(parse_eth_speed("25000\n"), Some(25e9));
+        // 链路 down 时内核写入 -1
+        assert_eq!(parse_eth_speed("-1\n"), None);
+        assert_eq!(parse_eth_speed(""), None);
+    }
+
+    #[test]
+    fn link_layer_distinguishes_infiniband_from_roce() {
+        assert_eq!(parse_link_layer("InfiniBand\n"), Some(LinkLayer::InfiniBand));
+        assert_eq!(parse_link_layer("Ethernet\n"), Some(LinkLayer::Roce));
+        assert_eq!(parse_link_layer("Unknown"), None);
+    }
+
+    #[tokio::test]
+    async fn discovers_ib_ports_and_physical_ethernet() {
+        let tree = TempTree::new("discover");
+        tree.ib_port("mlx5_1", "1");
+        tree.ib_port("mlx5_0", "1");
+        tree.ib_port("mlx5_0", "2");
+        tree.write("class/infiniband/mlx5_0/ports/1/rate", "100 Gb/sec (4X EDR)\n");
+        tree.eth_port("eth0", true);
+        tree.write("class/net/eth0/speed", "25000\n");
+        // 回环与没有 device 的虚拟接口默认不出现
+        tree.eth_port("lo", false);
+        tree.eth_port("docker0", false);
+
+        let ports = discover_ports(tree.path(), false).await.unwrap();
+        assert_eq!(summarize(&ports), vec![
+            ("eth0".to_string(), PortType::Ethernet, "eth0".to_string(), String::new(), Some(25e9)),
+            ("mlx5_0-1".to_string(), PortType::Rdma, "mlx5_0".to_string(), "1".to_string(), Some(100e9)),
+            ("mlx5_0-2".to_string(), PortType::Rdma, "mlx5_0".to_string(), "2".to_string(), None),
+            ("mlx5_1-1".to_string(), PortType::Rdma, "mlx5_1".to_string(), "1".to_string(), None),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn missing_class_dirs_yield_no_ports() {
+        let tree = TempTree::new("discover-empty");
+        assert!(discover_ports(tree.path(), false).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn virtual_interfaces_need_include_virtual() {
+        let tree = TempTree::new("discover-virtual");
+        tree.eth_port("eth0", true);
+        tree.eth_port("bond0", false);
+        tree.eth_port("lo", false);
+        let names = |ports: Vec<PortInfo>| ports.into_iter().map(|p| p.name).collect::<Vec<_>>();
+
+        assert_eq!(names(discover_ports(tree.path(), false).await.unwrap()), vec!["eth0"]);
+        // 回环接口即使打开开关也不会出现
+        assert_eq!(names(discover_ports(tree.path(), true).await.unwrap()), vec!["bond0", "eth0"]);
+    }
+}
@@ -1,5 +1,8 @@
 use std::fs::File;
-use std::io::{self, Read, Seek, SeekFrom};
+use std::io;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+use std::time::Instant;
 
 /// 专用于 sysfs 计数器文件的高性能读取器。
 /// 
@@ -7,6 +10,7 @@ use std::io::{self, Read, Seek, SeekFrom};
 /// 1. 避免重复 open/close 系统调用。
 /// 2. 避免 String 内存分配。
 /// 3. 使用字节级手动解析代替标准库 parse。
+/// 4. 使用 pread (`read_at`) 从偏移 0 读取，省掉每次的 lseek。
 pub struct FastSysfsReader {
     file: File,
     // 64字节的栈缓冲区足以容纳 u64::MAX (20位) + 换行符 + 冗余空间。
@@ -17,7 +21,7 @@ pub struct FastSysfsReader {
 impl FastSysfsReader {
     /// 打开指定路径的文件并准备读取。
     /// 仅在初始化时调用一次 open syscall。
-    pub fn new(path: &str) -> io::Result<Self> {
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
         let file = File::open(path)?;
         Ok(Self {
             file,
@@ -31,39 +35,151 @@ impl FastSysfsReader {
     /// 在 1ms 循环中，此函数的耗时通常在微秒(us)级别。
     #[inline(always)]
     pub fn read_u64(&mut self) -> io::Result<u64> {
-        // 1. 重置文件指针到开头 (lseek)
-        // 这是读取 sysfs 动态文件的必要操作。
-        self.file.seek(SeekFrom::Start(0))?;
-
-        // 2. 读取内容到栈缓冲区 (read)
-        let n = self.file.read(&mut self.buffer)?;
-        
-        if n == 0 {
-            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Empty sysfs file"));
+        read_counter(&self.file, &mut self.buffer)
+    }
+}
+
+/// 同一端口的一组计数器文件，每轮在一个紧凑循环里全部读完
+///
+/// 所有文件共用一块栈缓冲区，每个计数器只需一次 pread 系统调用。
+pub struct CounterGroup {
+    files: Vec<File>,
+    buffer: [u8; 64],
+}
+
+impl CounterGroup {
+    pub fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            buffer: [0u8; 64],
+        }
+    }
+
+    /// 打开一个计数器文件加入组内，返回它在读取结果中的下标
+    pub fn add(&mut self, path: impl AsRef<Path>) -> io::Result<usize> {
+        self.files.push(File::open(path)?);
+        Ok(self.files.len() - 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// 按加入顺序读取全部计数器，结果写入 `out` (先清空，复用其容量)
+    ///
+    /// 每个值附带自己读完的时刻，调用方可据此按各自的间隔计算速率；
+    /// 单个文件读取失败不影响组内其他文件。
+    pub fn read_all(&mut self, out: &mut Vec<io::Result<(u64, Instant)>>) {
+        out.clear();
+        for file in &self.files {
+            out.push(read_counter(file, &mut self.buffer).map(|value| (value, Instant::now())));
+        }
+    }
+}
+
+impl Default for CounterGroup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 从偏移 0 读取一个 sysfs 计数器文件并解析
+///
+/// sysfs 属性文件每次从头读取都会重新生成内容，pread 不移动文件指针，因此无需 seek。
+#[inline(always)]
+fn read_counter(file: &File, buffer: &mut [u8; 64]) -> io::Result<u64> {
+    let n = file.read_at(buffer, 0)?;
+    if n == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Empty sysfs file"));
+    }
+    parse_counter(&buffer[..n])
+}
+
+/// 手动字节解析 (Manual Byte Parsing)
+///
+/// 比 String::parse::<u64>() 快，因为：
+/// - 无需 UTF-8 有效性检查
+/// - 无需处理复杂的 Result/Option 包装链
+/// - 遇到非数字字符立即停止
+#[inline(always)]
+pub fn parse_counter(bytes: &[u8]) -> io::Result<u64> {
+    let mut num: u64 = 0;
+
+    // 使用迭代器切片，编译器通常能优化为非常高效的汇编指令
+    for &b in bytes {
+        if b.is_ascii_digit() {
+            // 累加数值: num = num * 10 + digit
+            num = num.wrapping_mul(10).wrapping_add((b - b'0') as u64);
+        } else if b == b'\n' || b == 0 || b == b' ' {
+            // 遇到换行、空字符或空格，视为结束
+            break;
+        } else {
+            // 遇到其他非法字符（如字母、符号），视为数据损坏
+            // 在极速模式下，与其猜测不如报错，防止脏数据污染图表
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Non-digit encountered"));
+        }
+    }
+
+    Ok(num)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempTree;
+
+    #[test]
+    fn reads_every_counter_in_the_group() {
+        const N: usize = 64;
+        let tree = TempTree::new("group");
+        let mut group = CounterGroup::new();
+        for i in 0..N {
+            let path = tree.write(&format!("c{}", i), &format!("{}\n", u64::MAX - i as u64));
+            assert_eq!(group.add(&path).unwrap(), i);
+        }
+        assert_eq!(group.len(), N);
+
+        let mut out = Vec::new();
+        let before = Instant::now();
+        for _ in 0..100 {
+            group.read_all(&mut out);
         }
+        let values: Vec<u64> = out.iter().map(|r| r.as_ref().unwrap().0).collect();
+        assert_eq!(values, (0..N as u64).map(|i| u64::MAX - i).collect::<Vec<_>>());
+        // 每个值带自己的读取时刻，按读取顺序单调不减
+        let times: Vec<Instant> = out.iter().map(|r| r.as_ref().unwrap().1).collect();
+        assert!(times[0] >= before && times.windows(2).all(|w| w[0] <= w[1]));
+    }
 
-        // 3. 手动字节解析 (Manual Byte Parsing)
-        // 比 String::parse::<u64>() 快，因为：
-        // - 无需 UTF-8 有效性检查
-        // - 无需处理复杂的 Result/Option 包装链
-        // - 遇到非数字字符立即停止
-        let mut num: u64 = 0;
-        
-        // 使用迭代器切片，编译器通常能优化为非常高效的汇编指令
-        for &b in &self.buffer[..n] {
-            if b >= b'0' && b <= b'9' {
-                // 累加数值: num = num * 10 + digit
-                num = num.wrapping_mul(10).wrapping_add((b - b'0') as u64);
-            } else if b == b'\n' || b == 0 || b == b' ' {
-                // 遇到换行、空字符或空格，视为结束
-                break;
-            } else {
-                // 遇到其他非法字符（如字母、符号），视为数据损坏
-                // 在极速模式下，与其猜测不如报错，防止脏数据污染图表
-                return Err(io::Error::new(io::ErrorKind::InvalidData, "Non-digit encountered"));
-            }
+    #[test]
+    fn per_slot_errors_do_not_affect_other_slots() {
+        let tree = TempTree::new("group");
+        let mut group = CounterGroup::new();
+        for (name, content) in [("ok", "42\n"), ("bad", "12ab\n"), ("empty", ""), ("last", "7")] {
+            group.add(tree.write(name, content)).unwrap();
         }
-        
-        Ok(num)
+        // 上一轮残留的结果会被清空
+        let mut out: Vec<io::Result<(u64, Instant)>> = (0..9).map(|_| Ok((0, Instant::now()))).collect();
+        group.read_all(&mut out);
+        assert_eq!(out.len(), 4);
+        assert_eq!(out[0].as_ref().unwrap().0, 42);
+        assert_eq!(out[1].as_ref().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(out[2].as_ref().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(out[3].as_ref().unwrap().0, 7);
+
+        assert!(group.add(tree.path().join("missing")).is_err());
+        assert_eq!(group.len(), 4);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn parse_counter_stops_at_terminators() {
+        assert_eq!(parse_counter(b"18446744073709551615\n").unwrap(), u64::MAX);
+        assert_eq!(parse_counter(b"123 456").unwrap(), 123);
+        assert_eq!(parse_counter(b"9\0garbage").unwrap(), 9);
+        assert_eq!(parse_counter(b"-1\n").unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
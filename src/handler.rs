@@ -1,19 +1,109 @@
 use crate::app::App;
+use crate::ui;
 use anyhow::Result;
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+// 同一秒内重复快照时追加的序号上限
+const MAX_SNAPSHOT_SUFFIX: u32 = 1000;
 
 pub fn handle_key_event(key_event: KeyEvent, app: &mut App) -> Result<()> {
+    // raw mode 下 Ctrl+C 不会产生 SIGINT，而是作为带 CONTROL 修饰的 'c' 到达；
+    // 帮助浮层打开时同样生效
+    if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+        if key_event.code == KeyCode::Char('c') {
+            app.quit();
+        }
+        // 其余 Ctrl 组合键不落到同名字母的绑定上 (如 Ctrl+S 不应切换排序)
+        return Ok(());
+    }
+
+    // 帮助浮层打开时只响应关闭与退出，其余按键不穿透到下层视图
+    if app.show_help {
+        match key_event.code {
+            KeyCode::Char('?') | KeyCode::Char('h') | KeyCode::Esc => app.toggle_help(),
+            KeyCode::Char('q') => app.quit(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
     match key_event.code {
         // 退出
-        KeyCode::Char('q') | KeyCode::Esc => {
+        KeyCode::Char('q') => {
             app.quit();
         }
+
+        // Esc 优先退出详情视图，网格视图下直接退出程序
+        KeyCode::Esc => {
+            if app.focused.is_some() {
+                app.close_focus();
+            } else {
+                app.quit();
+            }
+        }
+
+        // 进入/退出选中端口的详情视图
+        KeyCode::Enter => {
+            app.toggle_focus();
+        }
         
         // 切换视图
         KeyCode::Tab => {
             app.toggle_view_mode();
         }
 
+        // 切换 Bytes/s 与 Bits/s
+        KeyCode::Char('u') => {
+            app.toggle_unit();
+        }
+
+        // 暂停/恢复
+        KeyCode::Char(' ') | KeyCode::Char('p') => {
+            app.toggle_pause();
+        }
+
+        // 清空历史
+        KeyCode::Char('c') => {
+            app.clear_histories();
+        }
+
+        // 切换链路利用率叠加显示
+        KeyCode::Char('l') => {
+            app.toggle_utilization();
+        }
+
+        // 切换配色主题
+        KeyCode::Char('t') => {
+            app.cycle_theme();
+        }
+
+        // 显示快捷键帮助
+        KeyCode::Char('?') | KeyCode::Char('h') => {
+            app.toggle_help();
+        }
+
+        // 切换网格排序 (名称/RX/TX/总量)
+        KeyCode::Char('s') => {
+            app.cycle_sort();
+        }
+
+        // 把当前状态导出为文本文件；失败只在状态栏提示，不中断程序
+        KeyCode::Char('S') => {
+            let message = match write_snapshot(&app.snapshot()) {
+                Ok(path) => format!("Snapshot saved to {}", path.display()),
+                Err(e) => format!("Snapshot failed: {}", e),
+            };
+            app.notify(message);
+        }
+
+        // 切换窗口聚合策略 (峰值/平均/最后值)
+        KeyCode::Char('a') => {
+            app.cycle_aggregation();
+        }
+
         // --- 新增：滚动操作 ---
         // 向上滚动
         KeyCode::Up | KeyCode::Char('k') => {
@@ -27,4 +117,174 @@ pub fn handle_key_event(key_event: KeyEvent, app: &mut App) -> Result<()> {
         _ => {}
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn handle_mouse_event(mouse_event: MouseEvent, app: &mut App) -> Result<()> {
+    // 帮助浮层遮住了网格，点击与滚轮都不应作用到下层端口
+    if app.show_help {
+        return Ok(());
+    }
+
+    match mouse_event.kind {
+        // 左键点击：按网格布局换算出端口并选中
+        MouseEventKind::Down(MouseButton::Left) => {
+            if let Some(index) = ui::port_at(app, mouse_event.column, mouse_event.row) {
+                app.select_port(index);
+            }
+        }
+
+        // 滚轮与方向键一致
+        MouseEventKind::ScrollUp => {
+            app.on_up();
+        }
+        MouseEventKind::ScrollDown => {
+            app.on_down();
+        }
+
+        _ => {}
+    }
+    Ok(())
+}
+
+/// 写入当前目录下的 `rdma-dashboard-<时间>.txt`，同名文件已存在时依次尝试 `-1`、`-2` ...
+///
+/// 用 `create_new` 打开，保证不会覆盖已有的快照。
+fn write_snapshot(text: &str) -> io::Result<PathBuf> {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    create_snapshot(Path::new(""), &stamp, text)
+}
+
+/// 在 `dir` 下以 `stamp` 命名创建快照文件，必要时追加序号
+fn create_snapshot(dir: &Path, stamp: &str, text: &str) -> io::Result<PathBuf> {
+    for n in 0..MAX_SNAPSHOT_SUFFIX {
+        let path = dir.join(snapshot_file_name(stamp, n));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(text.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, "too many snapshots in the same second"))
+}
+
+fn snapshot_file_name(stamp: &str, n: u32) -> String {
+    if n == 0 {
+        format!("rdma-dashboard-{}.txt", stamp)
+    } else {
+        format!("rdma-dashboard-{}-{}.txt", stamp, n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::SortMode;
+    use crate::data::PortType;
+    use crate::test_support::{fake_history, test_app, TempTree};
+
+    fn press(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+        handle_key_event(KeyEvent::new(code, modifiers), app).unwrap();
+    }
+
+    #[tokio::test]
+    async fn ctrl_c_quits_instead_of_clearing() {
+        let tree = TempTree::new("keys");
+        tree.eth_port("eth0", true);
+        let mut app = test_app(&tree, &["--all"]).await;
+        app.histories = vec![fake_history("eth0", PortType::Ethernet, &[(1.0, 2.0)])];
+
+        press(&mut app, KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert!(app.should_quit);
+        assert_eq!(app.histories[0].read().unwrap().rx_data.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn ctrl_combinations_skip_letter_bindings() {
+        let tree = TempTree::new("keys");
+        tree.eth_port("eth0", true);
+        let mut app = test_app(&tree, &["--all"]).await;
+        app.histories = vec![fake_history("eth0", PortType::Ethernet, &[(1.0, 2.0)])];
+
+        press(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('p'), KeyModifiers::CONTROL);
+        assert_eq!(app.sort_mode, SortMode::Name);
+        assert!(!app.is_paused());
+
+        // 不带修饰键的 c 仍然是清空历史
+        press(&mut app, KeyCode::Char('c'), KeyModifiers::NONE);
+        assert!(!app.should_quit);
+        assert!(app.histories[0].read().unwrap().rx_data.is_empty());
+        press(&mut app, KeyCode::Char('s'), KeyModifiers::NONE);
+        assert_eq!(app.sort_mode, SortMode::Rx);
+    }
+
+    fn click(app: &mut App, column: u16, row: u16) {
+        let event = MouseEvent { kind: MouseEventKind::Down(MouseButton::Left), column, row, modifiers: KeyModifiers::NONE };
+        handle_mouse_event(event, app).unwrap();
+    }
+
+    #[tokio::test]
+    async fn clicks_select_then_focus_the_panel_under_the_cursor() {
+        let tree = TempTree::new("mouse");
+        tree.eth_port("eth0", true);
+        tree.eth_port("eth1", true);
+        let mut app = test_app(&tree, &["--all"]).await;
+        app.grid_area = ratatui::layout::Rect::new(0, 0, 80, 24);
+
+        // 每个面板 12 行高，第 13 行落在第二个面板上
+        click(&mut app, 5, 13);
+        assert_eq!((app.selected, app.focused), (1, None));
+        click(&mut app, 5, 13);
+        assert_eq!(app.focused, Some(1));
+
+        // 详情视图下点击不再命中网格
+        click(&mut app, 5, 1);
+        assert_eq!(app.selected, 1);
+    }
+
+    #[tokio::test]
+    async fn help_overlay_honours_ctrl_c_and_ignores_other_ctrl_keys() {
+        let tree = TempTree::new("keys");
+        tree.eth_port("eth0", true);
+        let mut app = test_app(&tree, &["--all"]).await;
+
+        press(&mut app, KeyCode::Char('?'), KeyModifiers::NONE);
+        assert!(app.show_help);
+        // Ctrl+H / Ctrl+Q 不是关闭或退出
+        press(&mut app, KeyCode::Char('h'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('q'), KeyModifiers::CONTROL);
+        assert!(app.show_help);
+        assert!(!app.should_quit);
+        // 其他按键被浮层吞掉
+        press(&mut app, KeyCode::Char('s'), KeyModifiers::NONE);
+        assert_eq!(app.sort_mode, SortMode::Name);
+
+        press(&mut app, KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn snapshot_names_are_numbered_on_collision() {
+        assert_eq!(snapshot_file_name("20260101-120000", 0), "rdma-dashboard-20260101-120000.txt");
+        assert_eq!(snapshot_file_name("20260101-120000", 2), "rdma-dashboard-20260101-120000-2.txt");
+
+        let tree = TempTree::new("snapshot");
+        let stamp = "20260101-120000";
+        let paths: Vec<PathBuf> = ["first", "second", "third"]
+            .iter()
+            .map(|text| create_snapshot(tree.path(), stamp, text).unwrap())
+            .collect();
+        let names: Vec<&str> = paths.iter().map(|p| p.file_name().unwrap().to_str().unwrap()).collect();
+        assert_eq!(names, vec![
+            "rdma-dashboard-20260101-120000.txt",
+            "rdma-dashboard-20260101-120000-1.txt",
+            "rdma-dashboard-20260101-120000-2.txt",
+        ]);
+        // 已有的快照不会被覆盖
+        assert_eq!(std::fs::read_to_string(&paths[0]).unwrap(), "first");
+        assert_eq!(std::fs::read_to_string(&paths[2]).unwrap(), "third");
+    }
+}
@@ -0,0 +1,107 @@
+use crate::app::{App, REDISCOVER_INTERVAL};
+use anyhow::Result;
+use std::io::{self, Write};
+
+/// 无界面模式：每个提交周期把新增的数据点以 NDJSON 打印到 stdout
+///
+/// 复用 TUI 模式下同一套 `spawn_chart_monitor` / `PortHistory` 数据管线，
+/// 通过 `PortHistory::committed` 计数判断哪些点尚未输出。
+pub async fn run(app: &mut App) -> Result<()> {
+    let metrics_server = app.spawn_metrics_server().await?;
+    let mut seen = vec![0u64; app.histories.len()];
+    let mut interval = tokio::time::interval(app.monitor_config.commit_interval);
+    let mut rediscover_interval = tokio::time::interval(REDISCOVER_INTERVAL);
+    rediscover_interval.tick().await;
+    // 信号监听只创建一次：每轮重新创建会丢掉写 stdout 期间到达的 SIGINT，
+    // 而默认处理已被替换，进程将不再退出
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {},
+            _ = rediscover_interval.tick() => {
+                if let Ok(added) = app.rediscover().await && added > 0 {
+                    seen.resize(app.histories.len(), 0);
+                }
+                continue;
+            },
+            _ = &mut ctrl_c => break,
+        }
+
+        let mut out = String::new();
+        for (history_lock, seen) in app.histories.iter().zip(seen.iter_mut()) {
+            let Ok(history) = history_lock.read() else { continue };
+
+            // 消费者落后超过缓冲区长度时，只能输出仍在缓冲区内的部分
+            let fresh = (history.committed - *seen).min(history.rx_data.len() as u64) as usize;
+            let skip = history.rx_data.len() - fresh;
+            let points = history.rx_data.iter().zip(history.tx_data.iter()).zip(history.timestamps.iter());
+            for ((&(_, rx), &(_, tx)), &ts) in points.skip(skip) {
+                out.push_str(&format_ndjson_line(&history.name, history.port_type.as_str(), ts, rx, tx));
+                out.push('\n');
+            }
+            *seen = history.committed;
+        }
+
+        if out.is_empty() {
+            continue;
+        }
+
+        // 每批立即 flush，保证在管道中可流式消费；下游关闭管道时安静退出
+        let mut stdout = io::stdout().lock();
+        match stdout.write_all(out.as_bytes()).and_then(|_| stdout.flush()) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => break,
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    if let Some(server) = metrics_server {
+        server.abort();
+    }
+    Ok(())
+}
+
+/// 生成一行 NDJSON 记录
+pub fn format_ndjson_line(iface: &str, port_type: &str, ts: u64, rx_bps: f64, tx_bps: f64) -> String {
+    format!(
+        r#"{{"iface":"{}","type":"{}","ts":{},"rx_bps":{:.0},"tx_bps":{:.0}}}"#,
+        escape_json(iface),
+        port_type,
+        ts,
+        rx_bps,
+        tx_bps
+    )
+}
+
+/// 接口名来自 sysfs 目录名，理论上不含特殊字符，这里仍做最小转义以保证 JSON 合法
+fn escape_json(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ndjson_line_has_fixed_field_order() {
+        let line = format_ndjson_line("mlx5_0-1", "rdma", 1_700_000_000_123, 1234.4, 0.6);
+        assert_eq!(line, r#"{"iface":"mlx5_0-1","type":"rdma","ts":1700000000123,"rx_bps":1234,"tx_bps":1}"#);
+    }
+
+    #[test]
+    fn interface_names_are_escaped() {
+        assert_eq!(escape_json(r#"a"b\c"#), r#"a\"b\\c"#);
+        assert_eq!(escape_json("eth\n0"), "eth\\u000a0");
+    }
+}
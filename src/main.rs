@@ -1,6 +1,14 @@
 mod app;
+mod config;
 mod data;
 mod handler;
+mod headless;
+mod metrics;
+mod record;
+mod summary;
+mod theme;
+#[cfg(test)]
+mod test_support;
 mod tui;
 mod ui;
 
@@ -8,12 +16,27 @@ mod ui;
 pub mod monitor;
 pub mod fast_io;
 
-use crate::app::App;
+use crate::app::{App, SHUTDOWN_TIMEOUT};
+use crate::data::{PortTypeFilter, DEFAULT_SYSFS_ROOT};
+use crate::config::{Config, FileConfig};
+use crate::monitor::Aggregation;
+use crate::theme::ThemeName;
+use crate::ui::SpeedUnit;
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use std::path::PathBuf;
+
+/// 输出方式
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Interactive terminal dashboard.
+    Tui,
+    /// Headless NDJSON stream on stdout, one object per committed sample.
+    Json,
+}
 
 #[derive(Parser, Debug)]
-#[group(required = true, multiple = false)]
+#[group(multiple = false)]
 struct Mode {
     /// Monitor all available RDMA ports.
     #[arg(short, long)]
@@ -30,11 +53,106 @@ pub struct Args {
     #[command(flatten)]
     mode: Mode,
 
-    /// Enable per-priority queue monitoring.
-    /// Note: In the current millisecond-precision mode, this flag might be ignored
-    /// to ensure system performance, as calling ethtool is too slow.
-    #[arg(short = 'q', long, default_value_t = false)]
+    /// Config file providing defaults for these options
+    /// [default: ~/.config/rdma-dashboard/config.toml]. Command-line flags take precedence.
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Only monitor ports of this type; applies to `--all` and `--interfaces`.
+    /// [default: both]
+    #[arg(long = "type", value_enum, value_name = "TYPE")]
+    port_type: Option<PortTypeFilter>,
+
+    /// Enable per-priority / per-queue monitoring for Ethernet ports.
+    /// Counters come from `ethtool -S`, polled every 500ms in a separate thread
+    /// so the 1ms byte-rate loop is unaffected. Adds a Queues view (Tab).
+    #[arg(short = 'q', long, default_value_t = false, overrides_with = "no_monitor_queues")]
     monitor_queues: bool,
+
+    /// Disable per-queue monitoring even if the config file enables it.
+    #[arg(long, default_value_t = false, overrides_with = "monitor_queues")]
+    no_monitor_queues: bool,
+
+    /// Also discover Ethernet interfaces without a backing device, such as
+    /// bond, team, VLAN and bridge interfaces (`lo` is always skipped).
+    #[arg(long, default_value_t = false, overrides_with = "no_include_virtual")]
+    include_virtual: bool,
+
+    /// Only discover physical interfaces even if the config file sets `include_virtual`.
+    #[arg(long, default_value_t = false, overrides_with = "include_virtual")]
+    no_include_virtual: bool,
+
+    /// Output format. `json` skips the TUI and streams NDJSON to stdout.
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Tui)]
+    output: OutputFormat,
+
+    /// Root of the sysfs tree to read counters from (useful in containers
+    /// that bind-mount the host sysfs elsewhere, or for fixture trees).
+    #[arg(long, env = "RDMA_DASH_SYSFS_ROOT", default_value = DEFAULT_SYSFS_ROOT)]
+    sysfs_root: PathBuf,
+
+    /// Counter sampling interval in microseconds. Larger values reduce CPU usage.
+    /// [default: 1000]
+    #[arg(long)]
+    sample_interval_us: Option<u64>,
+
+    /// Interval in milliseconds at which samples are aggregated into one chart point.
+    /// Must be >= the sample interval. [default: 10]
+    #[arg(long)]
+    commit_interval_ms: Option<u64>,
+
+    /// Number of points kept per port (window = points * commit interval).
+    /// [default: 600]
+    #[arg(long)]
+    history_points: Option<usize>,
+
+    /// UI refresh rate in frames per second, clamped to 1-60. Only affects redraws;
+    /// sampling, chart points and key handling are unchanged. [default: 10]
+    #[arg(long, value_name = "N")]
+    fps: Option<u32>,
+
+    /// Throughput display unit. `bits` shows Kb/s, Mb/s, Gb/s like NIC specs.
+    /// Can be toggled at runtime with `u`. [default: bytes]
+    #[arg(long, value_enum)]
+    units: Option<SpeedUnit>,
+
+    /// How samples within one commit interval are combined into a chart point.
+    /// Can be cycled at runtime with `a`. [default: peak]
+    #[arg(long, value_enum)]
+    aggregate: Option<Aggregation>,
+
+    /// Color theme. Can be cycled at runtime with `t`.
+    /// [default: dark, or mono when NO_COLOR is set]
+    #[arg(long, value_enum)]
+    theme: Option<ThemeName>,
+
+    /// Alert when RX or TX of a port exceeds this rate, e.g. `90Gb/s`, `10GB/s`.
+    /// A bare number is read in the `--units` unit.
+    #[arg(long, value_name = "RATE")]
+    alert_high: Option<String>,
+
+    /// Alert when both RX and TX of a port drop below this rate.
+    /// A bare number is read in the `--units` unit.
+    #[arg(long, value_name = "RATE")]
+    alert_low: Option<String>,
+
+    /// Append every committed sample to a CSV file
+    /// (`timestamp_ms,iface,type,rx_bps,tx_bps`, rates in Bytes/s).
+    #[arg(long, value_name = "PATH")]
+    record: Option<PathBuf>,
+
+    /// Sample for `--duration` seconds without the TUI, then print a per-port summary
+    /// (average, minimum and peak RX/TX, total bytes) to stdout. Ctrl+C ends early.
+    #[arg(long, default_value_t = false)]
+    once: bool,
+
+    /// Sampling time for `--once`, in seconds. [default: 10]
+    #[arg(long, value_name = "SECS", requires = "once", value_parser = clap::value_parser!(u64).range(1..))]
+    duration: Option<u64>,
+
+    /// Serve Prometheus metrics on `0.0.0.0:<PORT>/metrics`.
+    #[arg(long, value_name = "PORT")]
+    metrics_port: Option<u16>,
 }
 
 #[tokio::main]
@@ -42,15 +160,47 @@ async fn main() -> Result<()> {
     // 1. 解析参数
     let args = Args::parse();
 
-    // 2. 初始化终端 (RAII模式，自动处理进入/退出 raw mode)
-    let mut tui = tui::Tui::new()?;
+    // 合并配置文件：命令行 > 配置文件 > 内置默认值
+    let file = FileConfig::load(args.config.as_deref())?;
+    let config = Config::resolve(args, file)?;
+    let output = config.output;
+    let once = config.once;
 
-    // 3. 创建并初始化 App
+    // 2. 创建并初始化 App
     // 这里会启动后台的 1ms 硬核监控线程
-    let mut app = App::try_new(args).await?;
+    // 先于终端初始化执行，保证端口发现失败时错误信息正常打印
+    let mut app = App::try_new(config).await?;
+
+    // 3. 无界面模式：`--once` 打印汇总表，`-o json` 输出 NDJSON，均不进入 raw mode
+    let (result, stuck) = if let Some(duration) = once {
+        let result = summary::run(&mut app, duration).await;
+        (result, app.shutdown_monitors(SHUTDOWN_TIMEOUT))
+    } else if output == OutputFormat::Json {
+        let result = headless::run(&mut app).await;
+        (result, app.shutdown_monitors(SHUTDOWN_TIMEOUT))
+    } else {
+        // 4. 初始化终端 (RAII模式，自动处理进入/退出 raw mode)
+        let mut tui = tui::Tui::new()?;
+
+        // 5. 运行 App 主循环；先等监控线程退出，再恢复终端并输出统计
+        let result = app.run(&mut tui).await;
+        let stuck = app.shutdown_monitors(SHUTDOWN_TIMEOUT);
+        drop(tui);
+        (result, stuck)
+    };
+    if stuck > 0 {
+        eprintln!("{} monitor thread(s) did not stop within {:?}", stuck, SHUTDOWN_TIMEOUT);
+    }
 
-    // 4. 运行 App 主循环
-    app.run(&mut tui).await?;
+    // 6. 录制收尾：写完剩余数据，报告丢弃的点数
+    if let Some((path, summary)) = app.finish_recording()? {
+        eprintln!(
+            "Recorded {} samples to {} ({} dropped)",
+            summary.written,
+            path.display(),
+            summary.dropped
+        );
+    }
 
-    Ok(())
+    result
 }
\ No newline at end of file
@@ -0,0 +1,235 @@
+use crate::monitor::{PortHistory, RECONNECT_MAX, RECONNECT_MIN};
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// 请求头最大读取长度，Prometheus 的抓取请求远小于此
+const MAX_REQUEST_BYTES: usize = 8192;
+
+/// 读取请求头的时限；连上后不发数据的客户端到时即断开，不长期占用任务与文件描述符
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 绑定端口并在后台 tokio 任务中提供 `GET /metrics`
+///
+/// 绑定失败会立即返回错误；之后每个连接单独起一个任务处理，支持并发抓取。
+/// 只对 `PortHistory` 加读锁，不会阻塞 1ms 监控线程的 `try_write`。
+/// 端口列表通过 `watch` 通道获取，运行期间新发现的端口也会被导出。
+/// 调用方在退出时对返回的 `JoinHandle` 调用 `abort()` 即可关闭服务。
+pub async fn spawn_server(
+    port: u16,
+    histories: watch::Receiver<Vec<Arc<RwLock<PortHistory>>>>,
+) -> Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .await
+        .with_context(|| format!("Failed to bind metrics endpoint on port {}", port))?;
+    Ok(tokio::spawn(serve(listener, histories)))
+}
+
+/// 接受连接的主循环
+///
+/// accept 失败 (典型为文件描述符耗尽的 EMFILE) 时立即重试只会空转，
+/// 按与监控线程重连相同的退避间隔等待后再试。
+async fn serve(listener: TcpListener, histories: watch::Receiver<Vec<Arc<RwLock<PortHistory>>>>) {
+    let mut backoff = RECONNECT_MIN;
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                backoff = RECONNECT_MIN;
+                let histories = histories.borrow().clone();
+                tokio::spawn(async move {
+                    let _ = handle_connection(stream, &histories).await;
+                });
+            }
+            Err(e) => {
+                // 只在连续失败的第一次打印，避免刷屏
+                if backoff == RECONNECT_MIN {
+                    eprintln!("Metrics endpoint failed to accept a connection: {}", e);
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(RECONNECT_MAX);
+            }
+        }
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, histories: &[Arc<RwLock<PortHistory>>]) -> Result<()> {
+    let mut buf = vec![0u8; MAX_REQUEST_BYTES];
+    let Ok(len) = tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream, &mut buf)).await else {
+        // 超时直接丢弃连接
+        return Ok(());
+    };
+    let len = len?;
+
+    let request = String::from_utf8_lossy(&buf[..len]);
+    let mut parts = request.lines().next().unwrap_or("").split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+
+    let (status, content_type, body) = if method == "GET" && (path == "/metrics" || path.starts_with("/metrics?")) {
+        ("200 OK", "text/plain; version=0.0.4", render_metrics(histories))
+    } else {
+        ("404 Not Found", "text/plain", "Not Found\n".to_string())
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// 只需要请求行，读到头部结束、对端关闭或缓冲区满即可，返回读到的字节数
+async fn read_request(stream: &mut TcpStream, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        let n = stream.read(&mut buf[len..]).await?;
+        if n == 0 { break; }
+        len += n;
+        if buf[..len].windows(4).any(|w| w == b"\r\n\r\n") { break; }
+    }
+    Ok(len)
+}
+
+/// 以 Prometheus 文本格式导出每个端口最新一个数据点
+///
+/// 尚无数据与断线 (stale) 的端口不导出，避免已断开的链路仍显示最后的流量。
+pub fn render_metrics(histories: &[Arc<RwLock<PortHistory>>]) -> String {
+    let mut rx = String::new();
+    let mut tx = String::new();
+
+    for history_lock in histories {
+        let Ok(history) = history_lock.read() else { continue };
+        if history.stale {
+            continue;
+        }
+        let (last_rx, last_tx) = match (history.rx_data.back(), history.tx_data.back()) {
+            (Some((_, rx)), Some((_, tx))) => (*rx, *tx),
+            _ => continue,
+        };
+        let labels = format!(
+            r#"iface="{}",type="{}""#,
+            escape_label(&history.name),
+            history.port_type.as_str()
+        );
+        let _ = writeln!(rx, "rdma_rx_bytes_per_second{{{}}} {}", labels, last_rx);
+        let _ = writeln!(tx, "rdma_tx_bytes_per_second{{{}}} {}", labels, last_tx);
+    }
+
+    let mut out = String::new();
+    out.push_str("# HELP rdma_rx_bytes_per_second Latest committed RX throughput in bytes per second.\n");
+    out.push_str("# TYPE rdma_rx_bytes_per_second gauge\n");
+    out.push_str(&rx);
+    out.push_str("# HELP rdma_tx_bytes_per_second Latest committed TX throughput in bytes per second.\n");
+    out.push_str("# TYPE rdma_tx_bytes_per_second gauge\n");
+    out.push_str(&tx);
+    out
+}
+
+/// Prometheus label 值需要转义反斜杠、双引号和换行
+fn escape_label(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::PortType;
+    use crate::test_support::fake_history;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn exposition_lists_latest_point_per_port() {
+        let histories = vec![
+            fake_history("mlx5_0-1", PortType::Rdma, &[(1.0, 2.0), (1500.5, 3e9)]),
+            fake_history("eth0", PortType::Ethernet, &[(10.0, 0.0)]),
+        ];
+        assert_eq!(render_metrics(&histories), "\
+# HELP rdma_rx_bytes_per_second Latest committed RX throughput in bytes per second.
+# TYPE rdma_rx_bytes_per_second gauge
+rdma_rx_bytes_per_second{iface=\"mlx5_0-1\",type=\"rdma\"} 1500.5
+rdma_rx_bytes_per_second{iface=\"eth0\",type=\"ethernet\"} 10
+# HELP rdma_tx_bytes_per_second Latest committed TX throughput in bytes per second.
+# TYPE rdma_tx_bytes_per_second gauge
+rdma_tx_bytes_per_second{iface=\"mlx5_0-1\",type=\"rdma\"} 3000000000
+rdma_tx_bytes_per_second{iface=\"eth0\",type=\"ethernet\"} 0
+");
+    }
+
+    #[test]
+    fn empty_and_stale_ports_are_skipped() {
+        let stale = fake_history("eth1", PortType::Ethernet, &[(5.0, 5.0)]);
+        stale.write().unwrap().stale = true;
+        let histories = vec![fake_history("eth0", PortType::Ethernet, &[]), stale];
+        let text = render_metrics(&histories);
+        assert!(!text.contains("iface="), "{}", text);
+        assert_eq!(text.lines().count(), 4);
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(escape_label("eth0"), "eth0");
+        assert_eq!(escape_label(r#"a"b\c"#), r#"a\"b\\c"#);
+        assert_eq!(escape_label("line\nbreak"), "line\\nbreak");
+        let histories = vec![fake_history("we\"ird", PortType::Ethernet, &[(1.0, 1.0)])];
+        assert!(render_metrics(&histories).contains(r#"iface="we\"ird""#));
+    }
+
+    /// 发出一个原始 HTTP 请求并读完响应
+    async fn request(addr: std::net::SocketAddr, raw: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(raw.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn serves_metrics_and_404() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (_tx, rx) = watch::channel(vec![fake_history("eth0", PortType::Ethernet, &[(7.0, 8.0)])]);
+        let server = tokio::spawn(serve(listener, rx));
+
+        let ok = request(addr, "GET /metrics HTTP/1.1\r\nHost: x\r\n\r\n").await;
+        let (head, body) = ok.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"), "{}", head);
+        assert!(head.contains("Content-Type: text/plain; version=0.0.4"));
+        assert!(head.contains(&format!("Content-Length: {}", body.len())));
+        assert!(body.contains(r#"rdma_rx_bytes_per_second{iface="eth0",type="ethernet"} 7"#));
+
+        let query = request(addr, "GET /metrics?name=x HTTP/1.1\r\n\r\n").await;
+        assert!(query.starts_with("HTTP/1.1 200 OK\r\n"));
+
+        for raw in ["GET / HTTP/1.1\r\n\r\n", "POST /metrics HTTP/1.1\r\n\r\n"] {
+            let missing = request(addr, raw).await;
+            assert!(missing.starts_with("HTTP/1.1 404 Not Found\r\n"), "{}", missing);
+            assert!(missing.ends_with("\r\n\r\nNot Found\n"));
+        }
+        server.abort();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn silent_clients_are_dropped_after_the_timeout() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (_tx, rx) = watch::channel(Vec::new());
+        let server = tokio::spawn(serve(listener, rx));
+
+        // 只连接不发送：超时后服务端关闭连接，客户端读到 EOF 且没有响应
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let mut response = Vec::new();
+        let read = tokio::time::timeout(REQUEST_TIMEOUT * 2, stream.read_to_end(&mut response)).await;
+        assert!(read.is_ok(), "connection still open after {:?}", REQUEST_TIMEOUT * 2);
+        assert!(response.is_empty());
+        server.abort();
+    }
+}
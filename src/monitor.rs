@@ -1,38 +1,791 @@
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::{Arc, RwLock};
-use std::thread;
-use std::time::{Duration, Instant};
-use crate::fast_io::FastSysfsReader;
-use crate::data::PortType;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crate::fast_io::{CounterGroup, FastSysfsReader};
+use crate::data::{ib_sysfs_dir, net_sysfs_dir, LinkLayer, PortType};
+use crate::record::{RecordSink, Sample};
 
-// --- 配置常量 ---
-const HISTORY_CAPACITY: usize = 600; // 600点 * 10ms = 6秒历史
-const COMMIT_MS: u64 = 10;           // 10ms 聚合一次 (视觉精度)
-const TIME_STEP: f64 = 0.01;         // X轴每步 0.01s
+// --- 默认配置 (可通过 CLI 覆盖) ---
+pub const DEFAULT_HISTORY_POINTS: usize = 600; // 600点 * 10ms = 6秒历史
+pub const DEFAULT_COMMIT_MS: u64 = 10;         // 10ms 聚合一次 (视觉精度)
+pub const DEFAULT_SAMPLE_US: u64 = 1000;       // 1ms 采样 (物理极限)
+
+// --- 断线重连退避 ---
+pub const RECONNECT_MIN: Duration = Duration::from_millis(100);
+pub const RECONNECT_MAX: Duration = Duration::from_secs(2);
+
+// 低频线程休眠时检查退出信号的粒度
+const SHUTDOWN_POLL: Duration = Duration::from_millis(20);
+
+// --- 错误计数器 (低频轮询) ---
+const ERROR_POLL_MS: u64 = 1000;   // 错误计数器无需 1ms 精度，1s 轮询一次
+const ERROR_HISTORY: usize = 120;   // 保留最近 120 个轮询周期的增量
+
+/// RDMA 端口关注的错误计数器 (<counters>/ 下的文件名)
+const RDMA_ERROR_COUNTERS: &[&str] = &[
+    "symbol_error",
+    "port_rcv_errors",
+    "link_error_recovery",
+    "port_xmit_discards",
+];
+
+/// 以太网端口对应的错误计数器 (<statistics>/ 下的文件名)
+const ETH_ERROR_COUNTERS: &[&str] = &["rx_errors", "tx_errors", "rx_dropped", "tx_dropped"];
+
+// 每队列计数器来自 ethtool -S (子进程)，开销远大于 sysfs 读取，单独用慢线程轮询
+const QUEUE_POLL_MS: u64 = 500;
+
+/// ethtool -S 中的每队列/每优先级字节计数器，兼容常见驱动的命名：
+/// mlx5 `rx0_bytes` / `tx_prio3_bytes`，ixgbe/ice/virtio `rx_queue_0_bytes`，i40e `rx-0.bytes`
+static QUEUE_STAT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(rx|tx)[_-]?(queue_|prio)?(\d+)[_.]bytes:\s*(\d+)\s*$").unwrap()
+});
+
+/// IB 规范中 port_rcv_data / port_xmit_data 以 32-bit word (4 字节) 为单位
+const RDMA_WORD_BYTES: f64 = 4.0;
+
+/// 计数器原始单位到字节的换算系数
+/// - RDMA: sysfs 计数器单位是 32-bit word，需要 ×4
+/// - Ethernet: rx_bytes / tx_bytes 本身就是字节
+pub fn bytes_per_unit(p_type: PortType) -> f64 {
+    match p_type {
+        PortType::Rdma => RDMA_WORD_BYTES,
+        PortType::Ethernet => 1.0,
+    }
+}
+
+/// 计数器位宽
+/// 不少 mlx5 的 IB data 计数器只有 32 位，线速下几秒就会回绕一次
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterWidth {
+    Bits32,
+    Bits64,
+}
+
+impl CounterWidth {
+    /// 按端口类型给出初始猜测：RDMA 按 32 位处理，Ethernet 为 64 位
+    pub fn for_port_type(p_type: PortType) -> Self {
+        match p_type {
+            PortType::Rdma => CounterWidth::Bits32,
+            PortType::Ethernet => CounterWidth::Bits64,
+        }
+    }
+
+    /// 读数一旦超过 u32::MAX，说明驱动提供的是 64 位扩展计数器，自动升级
+    pub fn observe(self, value: u64) -> Self {
+        if value > u32::MAX as u64 { CounterWidth::Bits64 } else { self }
+    }
+}
+
+/// 32 位回绕判定窗口：prev 落在 u32 高半区时才认为是回绕，否则视为计数器复位
+const WRAP_WINDOW_32: u64 = 1 << 31;
+
+/// 计算两次读数之间的增量，处理 32 位计数器回绕
+///
+/// 返回 `None` 表示计数器被复位（驱动重载、手动清零等），调用方应丢弃该样本。
+pub fn counter_delta(prev: u64, curr: u64, width: CounterWidth) -> Option<u64> {
+    if curr >= prev {
+        return Some(curr - prev);
+    }
+    let max = u32::MAX as u64;
+    match width {
+        CounterWidth::Bits32 if prev <= max && prev >= max - WRAP_WINDOW_32 => {
+            Some((max - prev) + curr + 1)
+        }
+        _ => None,
+    }
+}
+
+/// 根据计数器增量计算速率 (Bytes/s)
+///
+/// 时间间隔过小时返回 `None`，避免除以接近 0 的值放大噪声。
+pub fn compute_speed(delta: u64, delta_time: f64, unit_multiplier: f64) -> Option<f64> {
+    if delta_time <= 0.000_001 {
+        return None;
+    }
+    Some((delta as f64 * unit_multiplier) / delta_time)
+}
+
+/// 可选的包计数器 (packets)
+///
+/// 与字节计数器不同，包计数器缺失（部分驱动/旧内核不提供）不应终止监控线程，
+/// 打开失败时不加入读取组，读取出错时只是跳过这一轮的 PPS。
+struct PacketCounter {
+    /// 在 `PortReaders::group` 读取结果中的下标
+    slot: usize,
+    /// 上一次读数及其读取时刻
+    prev: Option<(u64, Instant)>,
+    width: CounterWidth,
+}
+
+impl PacketCounter {
+    fn open(group: &mut CounterGroup, path: &std::path::Path, width: CounterWidth) -> Option<Self> {
+        let slot = group.add(path).ok()?;
+        Some(Self { slot, prev: None, width })
+    }
+
+    /// 用本轮读数更新并返回包速率 (packets/s)；首个读数、复位时返回 `None`
+    ///
+    /// 以本计数器自己的读取时刻计算间隔，不受同一轮中其他计数器读取耗时的影响。
+    fn update(&mut self, curr: u64, at: Instant) -> Option<f64> {
+        self.width = self.width.observe(curr);
+        let pps = self.prev.and_then(|(prev, prev_at)| {
+            let delta = counter_delta(prev, curr, self.width)?;
+            rate_since(delta, prev_at, at, 1.0)
+        });
+        self.prev = Some((curr, at));
+        pps
+    }
+}
+
+/// 一个提交窗口内多个采样值聚合成一个图表点的方式
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Aggregation {
+    /// Highest sample in the window; keeps short bursts visible.
+    #[default]
+    Peak,
+    /// Mean of the window; smoother, closer to sustained throughput.
+    Avg,
+    /// Most recent sample in the window.
+    Last,
+}
+
+impl Aggregation {
+    pub fn next(self) -> Self {
+        match self {
+            Aggregation::Peak => Aggregation::Avg,
+            Aggregation::Avg => Aggregation::Last,
+            Aggregation::Last => Aggregation::Peak,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Aggregation::Peak => "Peak",
+            Aggregation::Avg => "Avg",
+            Aggregation::Last => "Last",
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        self as u8
+    }
+
+    fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Aggregation::Avg,
+            2 => Aggregation::Last,
+            _ => Aggregation::Peak,
+        }
+    }
+}
+
+/// 单个提交窗口内的采样累积，同时维护最大值、总和与最后值，提交时按策略取值
+#[derive(Debug, Default, Clone, Copy)]
+struct Window {
+    max: f64,
+    sum: f64,
+    count: u32,
+    last: f64,
+}
+
+impl Window {
+    fn add(&mut self, v: f64) {
+        self.max = self.max.max(v);
+        self.sum += v;
+        self.count += 1;
+        self.last = v;
+    }
+
+    /// 窗口内没有有效采样时返回 0
+    fn value(&self, strategy: Aggregation) -> f64 {
+        match strategy {
+            Aggregation::Peak => self.max,
+            Aggregation::Avg if self.count > 0 => self.sum / self.count as f64,
+            Aggregation::Avg => 0.0,
+            Aggregation::Last => self.last,
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// 单个计数器相对其上一次读取时刻的速率
+///
+/// RX 与 TX 依次读取，各自的 seek+read 之间存在间隔；
+/// 每个方向使用自己的时间戳作为除数，避免共用一个时刻带来的系统性偏差。
+fn rate_since(delta: u64, prev_at: Instant, at: Instant, multiplier: f64) -> Option<f64> {
+    compute_speed(delta, at.saturating_duration_since(prev_at).as_secs_f64(), multiplier)
+}
+
+/// UI 与所有监控线程共享的运行时控制开关
+#[derive(Debug, Default)]
+pub struct MonitorControl {
+    paused: AtomicBool,
+    aggregation: AtomicU8,
+    shutdown: AtomicBool,
+}
+
+impl MonitorControl {
+    pub fn new(aggregation: Aggregation) -> Self {
+        Self {
+            paused: AtomicBool::new(false),
+            aggregation: AtomicU8::new(aggregation.to_u8()),
+            shutdown: AtomicBool::new(false),
+        }
+    }
+
+    /// 通知所有监控线程在下一次循环时退出
+    pub fn request_shutdown(&self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown.load(Ordering::Relaxed)
+    }
+
+    /// 分片休眠，期间收到退出信号时提前返回；返回 true 表示应退出
+    fn sleep_or_shutdown(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        loop {
+            if self.is_shutdown() {
+                return true;
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return false;
+            }
+            thread::sleep(remaining.min(SHUTDOWN_POLL));
+        }
+    }
+
+    pub fn aggregation(&self) -> Aggregation {
+        Aggregation::from_u8(self.aggregation.load(Ordering::Relaxed))
+    }
+
+    pub fn set_aggregation(&self, aggregation: Aggregation) {
+        self.aggregation.store(aggregation.to_u8(), Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+}
+
+/// 监控线程的运行配置，每个线程持有一份拷贝
+#[derive(Debug, Clone)]
+pub struct MonitorConfig {
+    /// sysfs 根目录，通常为 `/sys`
+    pub sysfs_root: PathBuf,
+    /// 计数器采样间隔
+    pub sample_interval: Duration,
+    /// 聚合提交间隔，同时决定 X 轴步长
+    pub commit_interval: Duration,
+    /// 每个端口保留的历史点数
+    pub history_points: usize,
+    /// 共享控制开关 (暂停等)
+    pub control: Arc<MonitorControl>,
+    /// `--record`：每个提交的点同时发往 CSV 写入线程
+    pub recorder: Option<RecordSink>,
+}
+
+impl MonitorConfig {
+    /// 校验参数组合的合理性
+    pub fn validate(&self) -> Result<()> {
+        if self.sample_interval.is_zero() {
+            anyhow::bail!("Sample interval must be greater than zero.");
+        }
+        if self.commit_interval < self.sample_interval {
+            anyhow::bail!(
+                "Commit interval ({:?}) must be >= sample interval ({:?}).",
+                self.commit_interval,
+                self.sample_interval
+            );
+        }
+        if self.history_points < 2 {
+            anyhow::bail!("History must hold at least 2 points.");
+        }
+        Ok(())
+    }
+}
+
+/// 单个错误计数器的累计值与各轮询周期的增量
+#[derive(Debug, Clone)]
+pub struct ErrorCounter {
+    pub name: &'static str,
+    pub total: u64,
+    pub deltas: std::collections::VecDeque<u64>,
+}
+
+impl ErrorCounter {
+    fn new(name: &'static str, total: u64) -> Self {
+        Self { name, total, deltas: std::collections::VecDeque::with_capacity(ERROR_HISTORY) }
+    }
+
+    /// 最近一个轮询周期的增量
+    pub fn last_delta(&self) -> u64 {
+        self.deltas.back().copied().unwrap_or(0)
+    }
+
+    /// 重新建立基线：记录新的累计值，本周期增量记 0
+    fn rebase(&mut self, total: u64) {
+        self.total = total;
+        self.push(total);
+    }
+
+    fn push(&mut self, total: u64) {
+        // 错误计数器在 IB 中是饱和而非回绕的，回退只可能是被清零，按 0 计
+        let delta = counter_delta(self.total, total, CounterWidth::Bits64).unwrap_or(0);
+        if self.deltas.len() >= ERROR_HISTORY {
+            self.deltas.pop_front();
+        }
+        self.deltas.push_back(delta);
+        self.total = total;
+    }
+}
+
+/// 单个队列 (或优先级) 的最新收发速率 (Bytes/s)
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueRate {
+    /// `prio<N>` 表示按优先级 (TC) 统计，`q<N>` 表示按硬件队列统计
+    pub name: String,
+    pub rx: f64,
+    pub tx: f64,
+}
+
+/// 从 `ethtool -S` 输出中提取每队列累计字节数 `(名称, rx, tx)`
+///
+/// 优先级计数器排在队列计数器之前，各自按编号升序；只出现单向计数器的队列另一向记 0。
+pub fn parse_queue_stats(output: &str) -> Vec<(String, u64, u64)> {
+    // key: (是否为硬件队列, 编号)，借助 BTreeMap 排序
+    let mut queues: std::collections::BTreeMap<(bool, u32), (u64, u64)> = Default::default();
+    for line in output.lines() {
+        let Some(caps) = QUEUE_STAT_RE.captures(line) else { continue };
+        let (Ok(index), Ok(value)) = (caps[3].parse::<u32>(), caps[4].parse::<u64>()) else { continue };
+        let is_queue = caps.get(2).is_none_or(|m| m.as_str() == "queue_");
+        let entry = queues.entry((is_queue, index)).or_default();
+        if &caps[1] == "rx" { entry.0 = value } else { entry.1 = value }
+    }
+    queues
+        .into_iter()
+        .map(|((is_queue, index), (rx, tx))| {
+            let name = if is_queue { format!("q{}", index) } else { format!("prio{}", index) };
+            (name, rx, tx)
+        })
+        .collect()
+}
+
+/// 单个序列 (RX 或 TX) 在缓冲窗口内的统计
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SeriesStats {
+    pub current: f64,
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+}
+
+impl SeriesStats {
+    /// 只统计数值分量，忽略时间分量；空序列返回全 0
+    pub fn from_series(data: &std::collections::VecDeque<(f64, f64)>) -> Self {
+        let Some(&(_, current)) = data.back() else {
+            return Self::default();
+        };
+        let (mut min, mut max, mut sum) = (f64::MAX, f64::MIN, 0.0);
+        for &(_, v) in data {
+            min = min.min(v);
+            max = max.max(v);
+            sum += v;
+        }
+        Self { current, min, max, avg: sum / data.len() as f64 }
+    }
+}
+
+/// 端口在缓冲窗口内的收发统计
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PortStats {
+    pub rx: SeriesStats,
+    pub tx: SeriesStats,
+}
 
 pub struct PortHistory {
     pub name: String,
     pub port_type: PortType,
+    pub capacity: usize,
+    /// 链路速率 (bits/s)，用于计算利用率；未知时为 None
+    pub link_rate_bps: Option<f64>,
+    /// RDMA 端口的链路层 (IB / RoCE)，仅用于标题；未知时为 None
+    pub link_layer: Option<LinkLayer>,
     pub rx_data: std::collections::VecDeque<(f64, f64)>, 
     pub tx_data: std::collections::VecDeque<(f64, f64)>,
+    /// 每个点提交时的墙钟时间 (Unix 毫秒)，与 rx_data 一一对应；
+    /// 图表仍用逻辑时间轴定位，只有坐标轴标签读取这里
+    pub timestamps: std::collections::VecDeque<u64>,
+    /// 包速率 (packets/s)，与 rx_data/tx_data 一一对应
+    pub rx_pps: std::collections::VecDeque<(f64, f64)>,
+    pub tx_pps: std::collections::VecDeque<(f64, f64)>,
+    /// 该端口是否提供包计数器；为 false 时 PPS 序列恒为 0
+    pub has_pps: bool,
+    /// 计数器文件不可读 (设备被移除/重置)，监控线程正在重连
+    pub stale: bool,
+    /// 错误计数器；设备上不存在的计数器不会出现在这里
+    pub errors: Vec<ErrorCounter>,
+    /// 累计提交的点数（单调递增，不受环形缓冲淘汰影响），供增量消费者判断新数据
+    pub committed: u64,
+    /// 监控启动以来累计收发的字节数 (已按端口类型换算)
+    pub total_rx_bytes: u64,
+    pub total_tx_bytes: u64,
+    /// 监控启动时刻，用于显示运行时长
+    pub started_at: Instant,
+    /// 每次 `clear()` 递增；监控线程据此发现清空并让本地时间轴从 0 重新开始
+    pub generation: u64,
+    /// 每队列速率 (仅 `-q` 且为以太网端口时由队列线程更新)；无法获取时为空
+    pub queues: Vec<QueueRate>,
+    /// 队列线程无法继续时的原因 (如找不到 ethtool)，由 Queues 视图显示
+    pub queue_error: Option<String>,
 }
 
 impl PortHistory {
-    pub fn new(name: String, port_type: PortType) -> Self {
+    pub fn new(name: String, port_type: PortType, capacity: usize) -> Self {
         Self {
             name,
             port_type,
-            rx_data: std::collections::VecDeque::with_capacity(HISTORY_CAPACITY),
-            tx_data: std::collections::VecDeque::with_capacity(HISTORY_CAPACITY),
+            capacity,
+            link_rate_bps: None,
+            link_layer: None,
+            rx_data: std::collections::VecDeque::with_capacity(capacity),
+            tx_data: std::collections::VecDeque::with_capacity(capacity),
+            timestamps: std::collections::VecDeque::with_capacity(capacity),
+            rx_pps: std::collections::VecDeque::with_capacity(capacity),
+            tx_pps: std::collections::VecDeque::with_capacity(capacity),
+            has_pps: false,
+            stale: false,
+            errors: Vec::new(),
+            committed: 0,
+            total_rx_bytes: 0,
+            total_tx_bytes: 0,
+            started_at: Instant::now(),
+            generation: 0,
+            queues: Vec::new(),
+            queue_error: None,
+        }
+    }
+
+    /// 缓冲窗口内的 min/max/avg 统计
+    pub fn stats(&self) -> PortStats {
+        PortStats {
+            rx: SeriesStats::from_series(&self.rx_data),
+            tx: SeriesStats::from_series(&self.tx_data),
+        }
+    }
+
+    /// 清空所有历史序列 (累计值保留)，图表从原点重新开始
+    pub fn clear(&mut self) {
+        self.rx_data.clear();
+        self.tx_data.clear();
+        self.timestamps.clear();
+        self.rx_pps.clear();
+        self.tx_pps.clear();
+        for counter in &mut self.errors {
+            counter.deltas.clear();
         }
+        self.generation += 1;
     }
     
-    pub fn push_point(&mut self, time: f64, rx: f64, tx: f64) {
-        if self.rx_data.len() >= HISTORY_CAPACITY {
+    pub fn push_point(&mut self, time: f64, timestamp_ms: u64, rx: f64, tx: f64) {
+        self.push_point_with_pps(time, timestamp_ms, rx, tx, 0.0, 0.0);
+    }
+
+    /// 同时提交字节速率与包速率，保证各序列长度一致
+    pub fn push_point_with_pps(&mut self, time: f64, timestamp_ms: u64, rx: f64, tx: f64, rx_pps: f64, tx_pps: f64) {
+        if self.rx_data.len() >= self.capacity {
             self.rx_data.pop_front();
             self.tx_data.pop_front();
+            self.timestamps.pop_front();
+            self.rx_pps.pop_front();
+            self.tx_pps.pop_front();
         }
         self.rx_data.push_back((time, rx));
         self.tx_data.push_back((time, tx));
+        self.timestamps.push_back(timestamp_ms);
+        self.rx_pps.push_back((time, rx_pps));
+        self.tx_pps.push_back((time, tx_pps));
+        self.committed += 1;
+    }
+}
+
+/// 当前墙钟时间 (Unix 毫秒)
+pub fn epoch_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 端口计数器所在目录
+/// - RDMA: `<root>/class/infiniband/<dev>/ports/<n>/counters`
+/// - Ethernet: `<root>/class/net/<dev>/statistics`
+fn counter_dir(config: &MonitorConfig, p_type: PortType, dev_part: &str, port_part: &str) -> PathBuf {
+    match p_type {
+        PortType::Rdma => ib_sysfs_dir(&config.sysfs_root)
+            .join(dev_part).join("ports").join(port_part).join("counters"),
+        PortType::Ethernet => net_sysfs_dir(&config.sysfs_root).join(dev_part).join("statistics"),
+    }
+}
+
+/// 错误监控线程中的单个计数器文件
+struct ErrorSlot {
+    name: &'static str,
+    path: PathBuf,
+    /// None 表示设备暂不可读，下次轮询时重新打开
+    reader: Option<FastSysfsReader>,
+    /// 是否已在 `PortHistory::errors` 中建立基线
+    tracked: bool,
+}
+
+impl ErrorSlot {
+    /// 读取一次，必要时先重新打开；返回 (读数, 是否为重新打开后的首个读数)
+    fn poll(&mut self) -> (Option<u64>, bool) {
+        let reopened = self.reader.is_none();
+        if reopened {
+            self.reader = FastSysfsReader::new(&self.path).ok();
+        }
+        let value = self.reader.as_mut().and_then(|r| r.read_u64().ok());
+        if value.is_none() {
+            self.reader = None;
+        }
+        (value, reopened)
+    }
+}
+
+/// 低频轮询错误计数器的后台线程
+///
+/// 与 1ms 的吞吐采样线程完全独立，不影响其精度。
+/// 缺失的计数器不显示，但每轮都会重新探测；一个都打不开时按重连退避间隔重试，
+/// 热插拔后出现的计数器也会加入 Errors 视图。
+pub fn spawn_error_monitor(
+    dev_part: String,
+    port_part: String,
+    p_type: PortType,
+    config: MonitorConfig,
+    history: Arc<RwLock<PortHistory>>
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let base = counter_dir(&config, p_type, &dev_part, &port_part);
+        let names = match p_type {
+            PortType::Rdma => RDMA_ERROR_COUNTERS,
+            PortType::Ethernet => ETH_ERROR_COUNTERS,
+        };
+
+        let mut slots: Vec<ErrorSlot> = names
+            .iter()
+            .map(|&name| ErrorSlot { name, path: base.join(name), reader: None, tracked: false })
+            .collect();
+
+        let interval = Duration::from_millis(ERROR_POLL_MS);
+        let mut reconnect_backoff = RECONNECT_MIN;
+        loop {
+            // 暂停期间不更新：恢复后的第一个增量会包含暂停期间新增的错误，不会丢失
+            if !config.control.is_paused() {
+                let values: Vec<(Option<u64>, bool)> = slots.iter_mut().map(ErrorSlot::poll).collect();
+                if let Ok(mut h) = history.write() {
+                    // h.errors 与已建立基线的 slot 按顺序一一对应
+                    let mut index = 0;
+                    for (slot, (value, reopened)) in slots.iter_mut().zip(values) {
+                        if !slot.tracked {
+                            // 首次读到的计数器以当时的读数为基线，不把之前的累计值当作增量
+                            if let Some(total) = value {
+                                h.errors.insert(index, ErrorCounter::new(slot.name, total));
+                                slot.tracked = true;
+                                index += 1;
+                            }
+                            continue;
+                        }
+                        let counter = &mut h.errors[index];
+                        index += 1;
+                        match value {
+                            // 重连后计数器可能已被驱动清零，重新建立基线
+                            Some(total) if reopened => counter.rebase(total),
+                            Some(total) => counter.push(total),
+                            // 读取失败时沿用上次的值，增量记 0
+                            None => counter.push(counter.total),
+                        }
+                    }
+                }
+            }
+
+            let wait = if slots.iter().any(|slot| slot.tracked) {
+                reconnect_backoff = RECONNECT_MIN;
+                interval
+            } else {
+                // 设备尚未就绪或正在热插拔：不退出线程，按退避间隔重新探测
+                let wait = reconnect_backoff;
+                reconnect_backoff = (reconnect_backoff * 2).min(RECONNECT_MAX);
+                wait
+            };
+            if config.control.sleep_or_shutdown(wait) {
+                return;
+            }
+        }
+    })
+}
+
+/// 执行 `ethtool -S <dev>` 并解析每队列计数器
+///
+/// ethtool 不存在时返回 `Err`，调用方据此放弃轮询；命令执行失败 (设备消失、驱动不支持) 返回 `Ok(None)`。
+fn read_queue_stats(dev: &str) -> std::io::Result<Option<Vec<(String, u64, u64)>>> {
+    let output = Command::new("ethtool").arg("-S").arg(dev).output()?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(parse_queue_stats(&String::from_utf8_lossy(&output.stdout))))
+}
+
+/// 队列线程放弃轮询时显示给用户的原因
+fn queue_error_message(dev: &str, error: &std::io::Error) -> String {
+    if error.kind() == std::io::ErrorKind::NotFound {
+        "`ethtool` not found in PATH; install it to see per-queue rates".to_string()
+    } else {
+        format!("Cannot run `ethtool -S {}`: {}", dev, error)
+    }
+}
+
+/// 每队列速率监控线程 (`-q`，仅以太网端口)
+///
+/// 与 1ms 字节速率循环完全独立，每 `QUEUE_POLL_MS` 调用一次 ethtool，
+/// 只更新 `PortHistory::queues`，不影响主循环的采样精度。
+pub fn spawn_queue_monitor(
+    dev_name: String,
+    config: MonitorConfig,
+    history: Arc<RwLock<PortHistory>>
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let interval = Duration::from_millis(QUEUE_POLL_MS);
+        // 上一次的累计值；为空表示尚无基线
+        let mut prev: HashMap<String, (u64, u64)> = HashMap::new();
+        let mut prev_time = Instant::now();
+
+        loop {
+            // 暂停期间丢弃基线，恢复后先重新采一次，不把暂停期间的总量摊成速率
+            if config.control.is_paused() {
+                prev.clear();
+                if config.control.sleep_or_shutdown(interval) {
+                    return;
+                }
+                continue;
+            }
+
+            let now = Instant::now();
+            let samples = match read_queue_stats(&dev_name) {
+                Ok(samples) => samples.unwrap_or_default(),
+                // 系统中没有 ethtool，后续轮询也不会成功，记下原因后退出
+                Err(e) => {
+                    if let Ok(mut h) = history.write() {
+                        h.queue_error = Some(queue_error_message(&dev_name, &e));
+                    }
+                    return;
+                }
+            };
+
+            if !prev.is_empty() || samples.is_empty() {
+                let delta_time = (now - prev_time).as_secs_f64();
+                let rate = |prev: Option<u64>, curr: u64| {
+                    prev.and_then(|p| counter_delta(p, curr, CounterWidth::Bits64))
+                        .and_then(|d| compute_speed(d, delta_time, 1.0))
+                        .unwrap_or(0.0)
+                };
+                let queues = samples
+                    .iter()
+                    .map(|(name, rx, tx)| {
+                        let base = prev.get(name);
+                        QueueRate {
+                            name: name.clone(),
+                            rx: rate(base.map(|b| b.0), *rx),
+                            tx: rate(base.map(|b| b.1), *tx),
+                        }
+                    })
+                    .collect();
+                if let Ok(mut h) = history.write() {
+                    h.queues = queues;
+                }
+            }
+
+            prev = samples.into_iter().map(|(name, rx, tx)| (name, (rx, tx))).collect();
+            prev_time = now;
+            if config.control.sleep_or_shutdown(interval) {
+                return;
+            }
+        }
+    })
+}
+
+/// 一个端口在 1ms 循环中使用的全部计数器，每轮通过同一个 `CounterGroup` 读完
+struct PortReaders {
+    /// 依次为 RX 字节、TX 字节，以及存在时的 RX / TX 包计数器
+    group: CounterGroup,
+    rx_pkt: Option<PacketCounter>,
+    tx_pkt: Option<PacketCounter>,
+    /// 本轮读取结果，跨轮复用以免每毫秒分配
+    reads: Vec<std::io::Result<(u64, Instant)>>,
+}
+
+/// 字节计数器在读取组中的固定下标
+const RX_SLOT: usize = 0;
+const TX_SLOT: usize = 1;
+
+/// 一轮读取的结果
+struct PortSample {
+    /// RX / TX 字节计数器及各自的读取时刻；任一读取失败时为 `None`
+    bytes: Option<((u64, Instant), (u64, Instant))>,
+    rx_pps: Option<f64>,
+    tx_pps: Option<f64>,
+}
+
+impl PortReaders {
+    /// 字节计数器是必需的，任一打开失败即返回 `None`；包计数器缺失时只报告字节速率
+    fn open(paths: &[PathBuf; 4], width: CounterWidth) -> Option<Self> {
+        let mut group = CounterGroup::new();
+        group.add(&paths[0]).ok()?;
+        group.add(&paths[1]).ok()?;
+        let rx_pkt = PacketCounter::open(&mut group, &paths[2], width);
+        let tx_pkt = PacketCounter::open(&mut group, &paths[3], width);
+        let reads = Vec::with_capacity(group.len());
+        Some(Self { group, rx_pkt, tx_pkt, reads })
+    }
+
+    fn has_pps(&self) -> bool {
+        self.rx_pkt.is_some() || self.tx_pkt.is_some()
+    }
+
+    /// 读取一轮全部计数器；包速率独立于字节计数器的成败
+    fn sample(&mut self) -> PortSample {
+        self.group.read_all(&mut self.reads);
+        let reads = &self.reads;
+        let pps = |counter: &mut Option<PacketCounter>| {
+            let counter = counter.as_mut()?;
+            let &(curr, at) = reads[counter.slot].as_ref().ok()?;
+            counter.update(curr, at)
+        };
+        PortSample {
+            rx_pps: pps(&mut self.rx_pkt),
+            tx_pps: pps(&mut self.tx_pkt),
+            bytes: match (&reads[RX_SLOT], &reads[TX_SLOT]) {
+                (Ok(rx), Ok(tx)) => Some((*rx, *tx)),
+                _ => None,
+            },
+        }
     }
 }
 
@@ -40,109 +793,197 @@ pub fn spawn_chart_monitor(
     dev_part: String,
     port_part: String,
     p_type: PortType,
+    config: MonitorConfig,
     history: Arc<RwLock<PortHistory>>
-) {
+) -> JoinHandle<()> {
     thread::spawn(move || {
         // 1. 路径与单位配置
-        let (rx_path, tx_path, unit_multiplier) = match p_type {
-            PortType::Rdma => {
-                let base = format!("/sys/class/infiniband/{}/ports/{}/counters", dev_part, port_part);
-                (
-                    format!("{}/port_rcv_data", base), 
-                    format!("{}/port_xmit_data", base),
-                    4.0 // RDMA word size
-                )
-            },
-            PortType::Ethernet => {
-                let base = format!("/sys/class/net/{}/statistics", dev_part);
-                (
-                    format!("{}/rx_bytes", base), 
-                    format!("{}/tx_bytes", base),
-                    1.0 // Ethernet byte size
-                )
-            }
+        let base = counter_dir(&config, p_type, &dev_part, &port_part);
+        let paths = match p_type {
+            PortType::Rdma => [
+                base.join("port_rcv_data"), base.join("port_xmit_data"),
+                base.join("port_rcv_packets"), base.join("port_xmit_packets"),
+            ],
+            PortType::Ethernet => [
+                base.join("rx_bytes"), base.join("tx_bytes"),
+                base.join("rx_packets"), base.join("tx_packets"),
+            ],
         };
+        let unit_multiplier = bytes_per_unit(p_type);
+        let iface: Arc<str> = history.read().map(|h| h.name.as_str().into()).unwrap_or_else(|_| "".into());
+        let initial_width = CounterWidth::for_port_type(p_type);
+        let mut rx_width = initial_width;
+        let mut tx_width = initial_width;
 
         // 2. 初始化读取器
-        let mut rx_reader = match FastSysfsReader::new(&rx_path) {
-            Ok(f) => f, Err(_) => return,
-        };
-        let mut tx_reader = match FastSysfsReader::new(&tx_path) {
-            Ok(f) => f, Err(_) => return,
-        };
+        // 打开失败不退出线程：端口标记为 stale，随后按退避间隔重试
+        let mut readers: Option<PortReaders> = None;
+        let mut reconnect_backoff = RECONNECT_MIN;
+        let mut next_reconnect = Instant::now();
 
         // 3. 状态变量
         let mut prev_rx: u64 = 0;
         let mut prev_tx: u64 = 0;
         let mut initialized = false; 
 
-        let loop_interval = Duration::from_micros(1000); // 采样依然是 1ms 硬核物理极限
-        let commit_interval = Duration::from_millis(COMMIT_MS); // 提交改为 10ms
-        
+        let loop_interval = config.sample_interval;
+        let commit_interval = config.commit_interval;
+        // X 轴步长由实际提交间隔推导，保证刻度与真实时间一致
+        let time_step = commit_interval.as_secs_f64();
+
         let mut next_tick = Instant::now();
         let mut last_commit_time = Instant::now();
         
-        // 逻辑时间轴 (0.00, 0.01, 0.02 ...)
+        // 逻辑时间轴 (0, step, 2*step ...)
         let mut logical_time_axis = 0.0;
+        let mut seen_generation = 0;
 
-        // 局部峰值保持器
-        let mut window_max_rx: f64 = 0.0;
-        let mut window_max_tx: f64 = 0.0;
-        let mut prev_sample_time = Instant::now();
+        // 窗口聚合器 (峰值/平均/最后值由 control 在提交时决定)
+        let mut window_rx = Window::default();
+        let mut window_tx = Window::default();
+        let mut window_rx_pps = Window::default();
+        let mut window_tx_pps = Window::default();
+        // RX / TX 各自上一次成功读取的时刻
+        let mut prev_rx_time = Instant::now();
+        let mut prev_tx_time = Instant::now();
 
-        // 预读取
-        if let (Ok(rx), Ok(tx)) = (rx_reader.read_u64(), tx_reader.read_u64()) {
-            prev_rx = rx;
-            prev_tx = tx;
-            initialized = true;
-        }
+        // 尚未写入 PortHistory 的累计字节数 (提交失败或暂停时暂存)
+        let mut pending_rx_bytes: u64 = 0;
+        let mut pending_tx_bytes: u64 = 0;
 
-        // 4. 循环
-        loop {
+        // 4. 循环 (收到退出信号后结束)
+        while !config.control.is_shutdown() {
             next_tick += loop_interval;
             let now = Instant::now();
 
-            let curr_rx_res = rx_reader.read_u64();
-            let curr_tx_res = tx_reader.read_u64();
-            
-            if initialized {
-                if let (Ok(curr_rx), Ok(curr_tx)) = (curr_rx_res, curr_tx_res) {
-                    let delta_time = (now - prev_sample_time).as_secs_f64();
-                    
-                    if delta_time > 0.000_001 {
-                        if curr_rx >= prev_rx && curr_tx >= prev_tx {
-                            // 计算瞬时速度 (1ms slice)
-                            let rx_speed = ((curr_rx - prev_rx) as f64 * unit_multiplier) / delta_time;
-                            let tx_speed = ((curr_tx - prev_tx) as f64 * unit_multiplier) / delta_time;
-
-                            // 峰值保持 (Peak Hold)
-                            if rx_speed > window_max_rx { window_max_rx = rx_speed; }
-                            if tx_speed > window_max_tx { window_max_tx = tx_speed; }
+            // 断线重连：重新打开原路径，成功后清空基线，避免把断线期间的差值当成流量
+            if readers.is_none() && now >= next_reconnect {
+                match PortReaders::open(&paths, initial_width) {
+                    Some(r) => {
+                        if let Ok(mut h) = history.write() {
+                            h.has_pps = r.has_pps();
+                            h.stale = false;
                         }
+                        readers = Some(r);
+                        initialized = false;
+                        rx_width = initial_width;
+                        tx_width = initial_width;
+                        reconnect_backoff = RECONNECT_MIN;
+                    }
+                    None => {
+                        if let Ok(mut h) = history.write() {
+                            h.stale = true;
+                        }
+                        next_reconnect = now + reconnect_backoff;
+                        reconnect_backoff = (reconnect_backoff * 2).min(RECONNECT_MAX);
                     }
-                    prev_rx = curr_rx;
-                    prev_tx = curr_tx;
                 }
-            } else {
-                 if let (Ok(rx), Ok(tx)) = (curr_rx_res, curr_tx_res) {
-                    prev_rx = rx;
-                    prev_tx = tx;
-                    initialized = true;
+            }
+
+            if let Some(r) = readers.as_mut() {
+                // 每个计数器读完立即打时间戳，RX / TX 各自计算间隔
+                let sample = r.sample();
+                if let Some(pps) = sample.rx_pps {
+                    window_rx_pps.add(pps);
+                }
+                if let Some(pps) = sample.tx_pps {
+                    window_tx_pps.add(pps);
+                }
+
+                match sample.bytes {
+                    Some(((curr_rx, rx_time), (curr_tx, tx_time))) => {
+                        if initialized {
+                            rx_width = rx_width.observe(curr_rx);
+                            tx_width = tx_width.observe(curr_tx);
+
+                            // 回绕由 counter_delta 还原，复位则丢弃
+                            let rx_delta = counter_delta(prev_rx, curr_rx, rx_width);
+                            let tx_delta = counter_delta(prev_tx, curr_tx, tx_width);
+                            if let Some(d) = rx_delta {
+                                pending_rx_bytes += (d as f64 * unit_multiplier) as u64;
+                            }
+                            if let Some(d) = tx_delta {
+                                pending_tx_bytes += (d as f64 * unit_multiplier) as u64;
+                            }
+
+                            // 计算瞬时速度 (1ms slice)
+                            if let (Some(rx_speed), Some(tx_speed)) = (
+                                rx_delta.and_then(|d| rate_since(d, prev_rx_time, rx_time, unit_multiplier)),
+                                tx_delta.and_then(|d| rate_since(d, prev_tx_time, tx_time, unit_multiplier)),
+                            ) {
+                                window_rx.add(rx_speed);
+                                window_tx.add(tx_speed);
+                            }
+                        }
+                        prev_rx = curr_rx;
+                        prev_tx = curr_tx;
+                        prev_rx_time = rx_time;
+                        prev_tx_time = tx_time;
+                        initialized = true;
+                    }
+                    _ => {
+                        // 设备消失 (驱动重载、VF 复位等)：标记 stale，进入重连流程
+                        readers = None;
+                        initialized = false;
+                        next_reconnect = now + reconnect_backoff;
+                        if let Ok(mut h) = history.write() {
+                            h.stale = true;
+                        }
+                    }
                 }
             }
-            prev_sample_time = now;
 
-            // 5. 提交逻辑 (10ms 一次)
+            // 5. 提交逻辑 (每个 commit_interval 一次)
             if now.duration_since(last_commit_time) >= commit_interval {
-                // 非阻塞提交：如果 UI 在读，这帧就先攒着，不丢峰值
-                if let Ok(mut h) = history.try_write() {
-                    h.push_point(logical_time_axis, window_max_rx, window_max_tx);
-                    
+                if config.control.is_paused() {
+                    // 暂停：丢弃本窗口，时间轴不前进，恢复后曲线紧接暂停前的最后一点
+                    window_rx.reset();
+                    window_tx.reset();
+                    window_rx_pps.reset();
+                    window_tx_pps.reset();
+                    last_commit_time = now;
+                } else if let Ok(mut h) = history.try_write() {
+                    // UI 清空了历史：时间轴归零，避免 X 轴从旧的偏移量开始
+                    if h.generation != seen_generation {
+                        seen_generation = h.generation;
+                        logical_time_axis = 0.0;
+                    }
+
+                    // 非阻塞提交：如果 UI 在读，这帧就先攒着，不丢峰值
+                    let strategy = config.control.aggregation();
+                    let (rx_value, tx_value) = (window_rx.value(strategy), window_tx.value(strategy));
+                    let timestamp_ms = epoch_millis();
+                    h.push_point_with_pps(
+                        logical_time_axis,
+                        timestamp_ms,
+                        rx_value, tx_value,
+                        window_rx_pps.value(strategy), window_tx_pps.value(strategy),
+                    );
+
+                    h.total_rx_bytes += pending_rx_bytes;
+                    h.total_tx_bytes += pending_tx_bytes;
+                    pending_rx_bytes = 0;
+                    pending_tx_bytes = 0;
+
                     // 只有成功提交才重置
-                    window_max_rx = 0.0;
-                    window_max_tx = 0.0;
+                    window_rx.reset();
+                    window_tx.reset();
+                    window_rx_pps.reset();
+                    window_tx_pps.reset();
                     last_commit_time = now;
-                    logical_time_axis += TIME_STEP;
+                    logical_time_axis += time_step;
+                    drop(h);
+
+                    // 录制只做非阻塞投递，放在锁外，避免延长 UI 等待
+                    if let Some(sink) = &config.recorder {
+                        sink.send(Sample {
+                            timestamp_ms,
+                            iface: iface.clone(),
+                            port_type: p_type,
+                            rx_bps: rx_value,
+                            tx_bps: tx_value,
+                        });
+                    }
                 }
             }
 
@@ -154,5 +995,380 @@ pub fn spawn_chart_monitor(
                 next_tick = Instant::now();
             }
         }
-    });
-}
\ No newline at end of file
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{wait_until, TempTree};
+
+    fn queue(name: &str, rx: u64, tx: u64) -> (String, u64, u64) {
+        (name.to_string(), rx, tx)
+    }
+
+    #[test]
+    fn queue_stats_are_parsed_per_driver() {
+        // 各驱动 `ethtool -S` 的真实输出片段，包含不应匹配的聚合计数器
+        let cases = [
+            ("mlx5", "NIC statistics:\n     rx_packets: 5009\n     rx_bytes: 741913\n     rx_vport_unicast_bytes: 700000\n     \
+                      rx0_packets: 4000\n     rx0_bytes: 600000\n     tx0_bytes: 120000\n     rx1_bytes: 141913\n     \
+                      tx1_bytes: 0\n     rx_prio0_bytes: 741913\n     tx_prio3_bytes: 120000\n",
+             vec![queue("prio0", 741913, 0), queue("prio3", 0, 120000), queue("q0", 600000, 120000), queue("q1", 141913, 0)]),
+            ("ixgbe", "NIC statistics:\n     rx_bytes_nic: 9000\n     tx_queue_0_packets: 12\n     tx_queue_0_bytes: 2048\n     \
+                       rx_queue_0_bytes: 4096\n     rx_queue_1_bytes: 1024\n     tx_queue_1_bytes: 512\n",
+             vec![queue("q0", 4096, 2048), queue("q1", 1024, 512)]),
+            ("virtio_net", "NIC statistics:\n     rx_queue_0_packets: 3\n     rx_queue_0_bytes: 300\n     tx_queue_0_bytes: 200\n",
+             vec![queue("q0", 300, 200)]),
+            ("i40e", "NIC statistics:\n     rx_unicast: 10\n     tx-0.packets: 4\n     tx-0.bytes: 400\n     \
+                      rx-0.bytes: 800\n     rx-1.bytes: 100\n     tx-1.bytes: 50\n",
+             vec![queue("q0", 800, 400), queue("q1", 100, 50)]),
+        ];
+        for (driver, output, expected) in cases {
+            assert_eq!(parse_queue_stats(output), expected, "{}", driver);
+        }
+    }
+
+    #[test]
+    fn queue_stats_are_ordered_by_kind_then_number() {
+        let output = "rx_queue_10_bytes: 10\nrx_queue_2_bytes: 2\ntx_prio7_bytes: 7\nrx_prio1_bytes: 1\n";
+        let names: Vec<String> = parse_queue_stats(output).into_iter().map(|(name, _, _)| name).collect();
+        assert_eq!(names, ["prio1", "prio7", "q2", "q10"]);
+    }
+
+    #[test]
+    fn queue_stats_without_per_queue_counters_are_empty() {
+        assert!(parse_queue_stats("NIC statistics:\n     rx_bytes: 10\n     tx_bytes: 20\n").is_empty());
+        assert!(parse_queue_stats("").is_empty());
+    }
+
+    #[test]
+    fn missing_ethtool_is_reported_by_name() {
+        let not_found = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert!(queue_error_message("eth0", &not_found).contains("`ethtool` not found"));
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert!(queue_error_message("eth0", &denied).starts_with("Cannot run `ethtool -S eth0`"));
+    }
+
+    #[test]
+    fn rdma_words_are_scaled_to_bytes() {
+        // 1000 个 32-bit word / 秒 = 4000 Bytes/s
+        let speed = compute_speed(1000, 1.0, bytes_per_unit(PortType::Rdma));
+        assert_eq!(speed, Some(4000.0));
+    }
+
+    #[test]
+    fn ethernet_bytes_are_not_scaled() {
+        let speed = compute_speed(1000, 0.5, bytes_per_unit(PortType::Ethernet));
+        assert_eq!(speed, Some(2000.0));
+    }
+
+    #[test]
+    fn near_zero_interval_yields_no_speed() {
+        assert_eq!(compute_speed(1000, 0.0, 1.0), None);
+        assert_eq!(compute_speed(1000, 0.000_000_5, 1.0), None);
+    }
+
+    #[test]
+    fn bits32_counter_wrap_is_recovered() {
+        // 读数序列跨过 u32::MAX：max-10 -> 5，真实增量为 10 + 5 + 1
+        let max = u32::MAX as u64;
+        assert_eq!(counter_delta(max - 10, 5, CounterWidth::Bits32), Some(16));
+        assert_eq!(counter_delta(max, 0, CounterWidth::Bits32), Some(1));
+        assert_eq!(counter_delta(5, 105, CounterWidth::Bits32), Some(100));
+    }
+
+    #[test]
+    fn decrease_from_lower_half_is_a_reset() {
+        assert_eq!(counter_delta(1000, 10, CounterWidth::Bits32), None);
+        // 判定窗口的边界：低于 max - WRAP_WINDOW_32 视为复位，达到则按回绕处理
+        let max = u32::MAX as u64;
+        assert_eq!(counter_delta(max - WRAP_WINDOW_32 - 1, 0, CounterWidth::Bits32), None);
+        assert_eq!(counter_delta(max - WRAP_WINDOW_32, 0, CounterWidth::Bits32), Some(WRAP_WINDOW_32 + 1));
+    }
+
+    #[test]
+    fn bits64_counter_never_wraps() {
+        let max = u32::MAX as u64;
+        assert_eq!(counter_delta(max - 10, 5, CounterWidth::Bits64), None);
+        assert_eq!(counter_delta(u64::MAX - 1, 3, CounterWidth::Bits64), None);
+        assert_eq!(counter_delta(max, max + 7, CounterWidth::Bits64), Some(7));
+    }
+
+    #[test]
+    fn observe_upgrades_width_past_u32() {
+        let max = u32::MAX as u64;
+        assert_eq!(CounterWidth::Bits32.observe(max), CounterWidth::Bits32);
+        assert_eq!(CounterWidth::Bits32.observe(max + 1), CounterWidth::Bits64);
+        // 一旦升级不会因较小的读数退回 32 位
+        assert_eq!(CounterWidth::Bits64.observe(0), CounterWidth::Bits64);
+        assert_eq!(CounterWidth::for_port_type(PortType::Rdma), CounterWidth::Bits32);
+        assert_eq!(CounterWidth::for_port_type(PortType::Ethernet), CounterWidth::Bits64);
+    }
+
+    #[test]
+    fn error_counter_keeps_bounded_deltas() {
+        let mut counter = ErrorCounter::new("rx_errors", 10);
+        assert_eq!(counter.last_delta(), 0);
+        counter.push(13);
+        assert_eq!((counter.total, counter.last_delta()), (13, 3));
+        // 计数器被清零时增量记 0，之后从新值继续累计
+        counter.push(2);
+        assert_eq!((counter.total, counter.last_delta()), (2, 0));
+        counter.push(7);
+        assert_eq!(counter.last_delta(), 5);
+
+        for _ in 0..ERROR_HISTORY {
+            counter.push(7);
+        }
+        assert_eq!(counter.deltas.len(), ERROR_HISTORY);
+        assert!(counter.deltas.iter().all(|&d| d == 0));
+    }
+
+    #[test]
+    fn validate_rejects_inconsistent_intervals() {
+        let config = |sample_us: u64, commit_ms: u64, history_points: usize| MonitorConfig {
+            sysfs_root: PathBuf::from("/sys"),
+            sample_interval: Duration::from_micros(sample_us),
+            commit_interval: Duration::from_millis(commit_ms),
+            history_points,
+            control: Arc::new(MonitorControl::default()),
+            recorder: None,
+        };
+        assert!(config(DEFAULT_SAMPLE_US, DEFAULT_COMMIT_MS, DEFAULT_HISTORY_POINTS).validate().is_ok());
+        assert!(config(10_000, 10, 2).validate().is_ok());
+        assert!(config(0, 10, 600).validate().is_err());
+        assert!(config(20_000, 10, 600).validate().is_err());
+        assert!(config(1000, 10, 1).validate().is_err());
+    }
+
+    #[test]
+    fn packet_counter_rates_use_byte_delta_math() {
+        let mut group = CounterGroup::new();
+        let tree = TempTree::new("pps");
+        let path = tree.write("rx_packets", "0\n");
+        let mut counter = PacketCounter::open(&mut group, &path, CounterWidth::Bits32).unwrap();
+        let t0 = Instant::now();
+        // 首个读数只建立基线
+        assert_eq!(counter.update(100, t0), None);
+        assert_eq!(counter.update(1100, t0 + Duration::from_millis(500)), Some(2000.0));
+        // 32 位回绕与字节计数器按同一规则恢复
+        let max = u32::MAX as u64;
+        assert_eq!(counter.update(max - 9, t0 + Duration::from_secs(1)), Some((max - 9 - 1100) as f64 * 2.0));
+        assert_eq!(counter.update(10, t0 + Duration::from_secs(2)), Some(20.0));
+        // 复位丢弃本次速率，并以新读数为基线
+        assert_eq!(counter.update(5, t0 + Duration::from_secs(3)), None);
+        assert_eq!(counter.update(15, t0 + Duration::from_secs(4)), Some(10.0));
+    }
+
+    #[test]
+    fn missing_packet_counters_still_open_byte_readers() {
+        let tree = TempTree::new("readers");
+        let paths = [
+            tree.write("rx_bytes", "10\n"),
+            tree.write("tx_bytes", "20\n"),
+            tree.path().join("rx_packets"),
+            tree.path().join("tx_packets"),
+        ];
+        let mut readers = PortReaders::open(&paths, CounterWidth::Bits64).unwrap();
+        assert!(!readers.has_pps());
+        let sample = readers.sample();
+        let ((rx, _), (tx, _)) = sample.bytes.unwrap();
+        assert_eq!((rx, tx), (10, 20));
+        assert_eq!((sample.rx_pps, sample.tx_pps), (None, None));
+
+        // 只有一个方向的包计数器时仍报告 pps
+        tree.write("tx_packets", "0\n");
+        assert!(PortReaders::open(&paths, CounterWidth::Bits64).unwrap().has_pps());
+
+        // 字节计数器是必需的
+        std::fs::remove_file(&paths[1]).unwrap();
+        assert!(PortReaders::open(&paths, CounterWidth::Bits64).is_none());
+    }
+
+    #[test]
+    fn pps_series_stay_aligned_with_byte_series() {
+        let mut history = PortHistory::new("eth0".to_string(), PortType::Ethernet, 2);
+        history.push_point_with_pps(0.0, 0, 1.0, 2.0, 10.0, 20.0);
+        history.push_point(1.0, 0, 3.0, 4.0);
+        history.push_point_with_pps(2.0, 0, 5.0, 6.0, 50.0, 60.0);
+        assert_eq!(history.rx_pps, [(1.0, 0.0), (2.0, 50.0)]);
+        assert_eq!(history.tx_pps, [(1.0, 0.0), (2.0, 60.0)]);
+        assert_eq!(history.rx_data.len(), 2);
+        assert_eq!(history.committed, 3);
+    }
+
+    #[test]
+    fn timestamps_are_evicted_with_their_points() {
+        let mut history = PortHistory::new("eth0".to_string(), PortType::Ethernet, 2);
+        for (i, ts) in [1_000, 2_000, 3_000].into_iter().enumerate() {
+            history.push_point(i as f64, ts, 0.0, 0.0);
+        }
+        assert_eq!(history.timestamps, [2_000, 3_000]);
+        history.clear();
+        assert!(history.timestamps.is_empty());
+    }
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    fn test_config(tree: &TempTree) -> MonitorConfig {
+        MonitorConfig {
+            sysfs_root: tree.path().to_path_buf(),
+            sample_interval: Duration::from_millis(1),
+            commit_interval: Duration::from_millis(10),
+            history_points: 100,
+            control: Arc::new(MonitorControl::new(Aggregation::Peak)),
+            recorder: None,
+        }
+    }
+
+    fn eth_history() -> Arc<RwLock<PortHistory>> {
+        Arc::new(RwLock::new(PortHistory::new("eth0".to_string(), PortType::Ethernet, 100)))
+    }
+
+    #[test]
+    fn chart_monitor_reconnects_with_fresh_baseline() {
+        let tree = TempTree::new("reconnect");
+        tree.eth_port("eth0", true);
+        let rx = "class/net/eth0/statistics/rx_bytes";
+        tree.set_counter(rx, 1000);
+        let config = test_config(&tree);
+        let control = config.control.clone();
+        let history = eth_history();
+        let handle = spawn_chart_monitor("eth0".into(), String::new(), PortType::Ethernet, config, history.clone());
+        let read = |f: fn(&PortHistory) -> bool| wait_until(WAIT, || f(&history.read().unwrap()));
+
+        assert!(read(|h| h.committed >= 2));
+        tree.set_counter(rx, 1300);
+        assert!(read(|h| h.total_rx_bytes == 300));
+
+        // 已打开的文件读到空内容、路径也已消失：等同于设备被移除
+        tree.write(rx, "");
+        tree.remove(rx);
+        assert!(read(|h| h.stale));
+
+        // 设备回来时计数器已变成完全不同的值，不能把差值当作流量
+        tree.write(rx, &format!("{:020}\n", 1_000_000_000_000u64));
+        assert!(read(|h| !h.stale));
+        tree.set_counter(rx, 1_000_000_000_500);
+        assert!(read(|h| h.total_rx_bytes >= 800));
+        assert_eq!(history.read().unwrap().total_rx_bytes, 800);
+
+        control.request_shutdown();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn error_monitor_waits_for_counters_to_appear() {
+        let tree = TempTree::new("errors");
+        let config = test_config(&tree);
+        let control = config.control.clone();
+        let history = eth_history();
+        let handle = spawn_error_monitor("eth0".into(), String::new(), PortType::Ethernet, config, history.clone());
+
+        // 启动时一个计数器都没有，线程不应退出
+        thread::sleep(Duration::from_millis(150));
+        assert!(!handle.is_finished());
+        assert!(history.read().unwrap().errors.is_empty());
+
+        tree.write("class/net/eth0/statistics/tx_errors", "7\n");
+        assert!(wait_until(WAIT, || history.read().unwrap().errors.len() == 1));
+        // 后出现的计数器按固定顺序插入，且以出现时的读数为基线
+        tree.write("class/net/eth0/statistics/rx_errors", "3\n");
+        assert!(wait_until(WAIT, || history.read().unwrap().errors.len() == 2));
+        {
+            let h = history.read().unwrap();
+            let names: Vec<_> = h.errors.iter().map(|c| (c.name, c.total, c.last_delta())).collect();
+            assert_eq!(names, vec![("rx_errors", 3, 0), ("tx_errors", 7, 0)]);
+        }
+
+        control.request_shutdown();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn paused_monitor_commits_nothing_until_resumed() {
+        let tree = TempTree::new("pause");
+        tree.eth_port("eth0", true);
+        let config = test_config(&tree);
+        let control = config.control.clone();
+        control.set_paused(true);
+        let history = eth_history();
+        let handle = spawn_chart_monitor("eth0".into(), String::new(), PortType::Ethernet, config, history.clone());
+
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(history.read().unwrap().committed, 0);
+        control.set_paused(false);
+        assert!(wait_until(WAIT, || history.read().unwrap().committed > 0));
+
+        control.request_shutdown();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn window_value_per_strategy() {
+        let mut window = Window::default();
+        for v in [3.0, 9.0, 6.0] {
+            window.add(v);
+        }
+        assert_eq!(window.value(Aggregation::Peak), 9.0);
+        assert_eq!(window.value(Aggregation::Avg), 6.0);
+        assert_eq!(window.value(Aggregation::Last), 6.0);
+
+        // 空窗口 (含 reset 之后) 各策略都为 0，平均值不会除以 0
+        window.reset();
+        for strategy in [Aggregation::Peak, Aggregation::Avg, Aggregation::Last] {
+            assert_eq!(window.value(strategy), 0.0);
+        }
+    }
+
+    #[test]
+    fn chart_monitor_stops_on_shutdown_request() {
+        let tree = TempTree::new("shutdown");
+        tree.eth_port("eth0", true);
+        let config = test_config(&tree);
+        let control = config.control.clone();
+        let history = eth_history();
+        let handle = spawn_chart_monitor("eth0".into(), String::new(), PortType::Ethernet, config, history.clone());
+        assert!(wait_until(WAIT, || history.read().unwrap().committed > 0));
+
+        control.request_shutdown();
+        assert!(wait_until(crate::app::SHUTDOWN_TIMEOUT, || handle.is_finished()));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn series_stats_of_empty_series_are_zero() {
+        assert_eq!(SeriesStats::from_series(&std::collections::VecDeque::new()), SeriesStats::default());
+        let history = PortHistory::new("eth0".into(), PortType::Ethernet, 10);
+        assert_eq!(history.stats(), PortStats::default());
+    }
+
+    #[test]
+    fn series_stats_min_max_avg_and_current() {
+        let mut history = PortHistory::new("eth0".into(), PortType::Ethernet, 4);
+        for (i, (rx, tx)) in [(8.0, 1.0), (2.0, 1.0), (6.0, 3.0), (4.0, 7.0)].into_iter().enumerate() {
+            history.push_point(i as f64, 0, rx, tx);
+        }
+        let stats = history.stats();
+        assert_eq!(stats.rx, SeriesStats { current: 4.0, min: 2.0, max: 8.0, avg: 5.0 });
+        assert_eq!(stats.tx, SeriesStats { current: 7.0, min: 1.0, max: 7.0, avg: 3.0 });
+
+        // 只统计缓冲区内的点：最早的 8.0 被淘汰后不再计入最大值
+        history.push_point(4.0, 0, 0.0, 0.0);
+        assert_eq!(history.stats().rx, SeriesStats { current: 0.0, min: 0.0, max: 6.0, avg: 3.0 });
+    }
+
+    #[test]
+    fn each_direction_uses_its_own_interval() {
+        // 同一轮中 RX 先读、TX 后读：两者相对各自上一次读取的间隔不同
+        let t0 = Instant::now();
+        let (prev_rx, prev_tx) = (t0, t0 + Duration::from_micros(200));
+        let (rx_at, tx_at) = (t0 + Duration::from_millis(1), t0 + Duration::from_micros(1700));
+        let multiplier = bytes_per_unit(PortType::Rdma);
+        assert_eq!(rate_since(250, prev_rx, rx_at, multiplier), Some(1_000_000.0));
+        assert_eq!(rate_since(250, prev_tx, tx_at, multiplier), Some(250.0 * 4.0 / 0.0015));
+        // 时钟读数倒退 (同一时刻或更早) 时不产生速率
+        assert_eq!(rate_since(250, rx_at, prev_rx, multiplier), None);
+    }
+}
@@ -0,0 +1,237 @@
+use crate::data::PortType;
+use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+pub const CSV_HEADER: &str = "timestamp_ms,iface,type,rx_bps,tx_bps";
+
+// 通道容量：按 10ms 提交、每端口 100 点/秒计，足够缓冲数秒的磁盘抖动
+const RECORD_QUEUE: usize = 8192;
+// 写入线程的刷盘间隔
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+// 空闲时检查停止标志的粒度，决定退出时最多等待多久
+const STOP_POLL: Duration = Duration::from_millis(50);
+
+/// 一个已提交的数据点
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub timestamp_ms: u64,
+    pub iface: Arc<str>,
+    pub port_type: PortType,
+    pub rx_bps: f64,
+    pub tx_bps: f64,
+}
+
+/// 监控线程持有的发送端
+///
+/// 只做 `try_send`，通道满 (磁盘跟不上) 时丢弃并计数，绝不阻塞 1ms 采样循环。
+#[derive(Debug, Clone)]
+pub struct RecordSink {
+    tx: SyncSender<Sample>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl RecordSink {
+    pub fn send(&self, sample: Sample) {
+        match self.tx.try_send(sample) {
+            Ok(()) => {}
+            // 写入线程已因 I/O 错误退出时同样计为丢弃
+            Err(TrySendError::Full(_)) | Err(TrySendError::Disconnected(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// 录制结束时的统计
+#[derive(Debug, Clone, Copy)]
+pub struct RecordSummary {
+    pub written: u64,
+    pub dropped: u64,
+}
+
+/// `--record` 的 CSV 写入端，在独立线程中缓冲写盘
+pub struct Recorder {
+    path: PathBuf,
+    sink: RecordSink,
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<std::io::Result<u64>>,
+}
+
+impl Recorder {
+    /// 以追加方式打开文件；文件为空时先写表头，已有内容时直接续写
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open record file {}", path.display()))?;
+        let needs_header = file.metadata().map(|m| m.len() == 0).unwrap_or(true);
+
+        let (tx, rx) = mpsc::sync_channel(RECORD_QUEUE);
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let stop = stop.clone();
+            thread::spawn(move || write_loop(file, needs_header, rx, &stop))
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            sink: RecordSink { tx, dropped: Arc::new(AtomicU64::new(0)) },
+            stop,
+            handle,
+        })
+    }
+
+    pub fn sink(&self) -> RecordSink {
+        self.sink.clone()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 写完通道中剩余的数据并刷盘
+    ///
+    /// 监控线程仍持有发送端，因此用停止标志而不是通道关闭来结束写入线程。
+    pub fn finish(self) -> Result<RecordSummary> {
+        self.stop.store(true, Ordering::Relaxed);
+        let written = self
+            .handle
+            .join()
+            .map_err(|_| anyhow::anyhow!("Record writer thread panicked"))?
+            .with_context(|| format!("Failed to write record file {}", self.path.display()))?;
+        Ok(RecordSummary { written, dropped: self.sink.dropped.load(Ordering::Relaxed) })
+    }
+}
+
+fn write_loop(file: File, needs_header: bool, rx: Receiver<Sample>, stop: &AtomicBool) -> std::io::Result<u64> {
+    let mut out = BufWriter::new(file);
+    if needs_header {
+        writeln!(out, "{}", CSV_HEADER)?;
+    }
+
+    let mut written = 0;
+    let mut last_flush = Instant::now();
+    while !stop.load(Ordering::Relaxed) {
+        match rx.recv_timeout(STOP_POLL) {
+            Ok(sample) => {
+                writeln!(out, "{}", format_csv_line(&sample))?;
+                written += 1;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if last_flush.elapsed() >= FLUSH_INTERVAL {
+            out.flush()?;
+            last_flush = Instant::now();
+        }
+    }
+
+    // 退出前写完已排队的数据
+    while let Ok(sample) = rx.try_recv() {
+        writeln!(out, "{}", format_csv_line(&sample))?;
+        written += 1;
+    }
+    out.flush()?;
+    Ok(written)
+}
+
+/// 生成一行 CSV 记录 (不含换行)，列顺序与 `CSV_HEADER` 一致
+pub fn format_csv_line(sample: &Sample) -> String {
+    format!(
+        "{},{},{},{:.0},{:.0}",
+        sample.timestamp_ms,
+        escape_csv(&sample.iface),
+        sample.port_type.as_str(),
+        sample.rx_bps,
+        sample.tx_bps
+    )
+}
+
+/// 接口名通常不含逗号或引号，出现时按 RFC 4180 加引号
+fn escape_csv(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempTree;
+
+    fn sample(timestamp_ms: u64, iface: &str, port_type: PortType, rx_bps: f64, tx_bps: f64) -> Sample {
+        Sample { timestamp_ms, iface: iface.into(), port_type, rx_bps, tx_bps }
+    }
+
+    #[test]
+    fn recorded_csv_round_trips() {
+        let tree = TempTree::new("record");
+        let path = tree.path().join("out.csv");
+        let recorder = Recorder::create(&path).unwrap();
+        let sink = recorder.sink();
+        sink.send(sample(1000, "eth0", PortType::Ethernet, 1.4, 2.6));
+        sink.send(sample(1010, "mlx5_0-1", PortType::Rdma, 3e9, 0.0));
+        sink.send(sample(1020, "odd,name", PortType::Ethernet, 5.0, 6.0));
+        let summary = recorder.finish().unwrap();
+        assert_eq!((summary.written, summary.dropped), (3, 0));
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines, vec![
+            CSV_HEADER,
+            "1000,eth0,ethernet,1,3",
+            "1010,mlx5_0-1,rdma,3000000000,0",
+            "1020,\"odd,name\",ethernet,5,6",
+        ]);
+
+        // 续写已有文件时不重复表头
+        let recorder = Recorder::create(&path).unwrap();
+        recorder.sink().send(sample(1030, "eth0", PortType::Ethernet, 7.0, 8.0));
+        recorder.finish().unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.matches(CSV_HEADER).count(), 1);
+        assert_eq!(text.lines().last(), Some("1030,eth0,ethernet,7,8"));
+    }
+
+    #[test]
+    fn full_channel_counts_dropped_samples() {
+        // 没有写入线程消费的小通道：超出容量的样本被丢弃而不是阻塞
+        let (tx, rx) = mpsc::sync_channel(2);
+        let sink = RecordSink { tx, dropped: Arc::new(AtomicU64::new(0)) };
+        for i in 0..5 {
+            sink.send(sample(i, "eth0", PortType::Ethernet, 0.0, 0.0));
+        }
+        assert_eq!(sink.dropped.load(Ordering::Relaxed), 3);
+        assert_eq!(rx.try_iter().map(|s| s.timestamp_ms).collect::<Vec<_>>(), vec![0, 1]);
+
+        // 写入线程已退出时同样计为丢弃
+        drop(rx);
+        sink.send(sample(5, "eth0", PortType::Ethernet, 0.0, 0.0));
+        assert_eq!(sink.dropped.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn finish_reports_every_sample_as_written_or_dropped() {
+        let tree = TempTree::new("record");
+        let recorder = Recorder::create(&tree.path().join("burst.csv")).unwrap();
+        let sink = recorder.sink();
+        let sent = RECORD_QUEUE as u64 * 4;
+        for i in 0..sent {
+            sink.send(sample(i, "eth0", PortType::Ethernet, 0.0, 0.0));
+        }
+        let summary = recorder.finish().unwrap();
+        assert_eq!(summary.written + summary.dropped, sent);
+        let lines = std::fs::read_to_string(tree.path().join("burst.csv")).unwrap().lines().count() as u64;
+        assert_eq!(lines, summary.written + 1);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::app::App;
+use crate::monitor::PortHistory;
+use crate::ui::{format_bytes, format_speed, SpeedUnit};
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// 未指定 `--duration` 时的采样时长 (秒)
+pub const DEFAULT_ONCE_SECS: u64 = 10;
+
+// 汇总表中速率列与累计列的宽度
+const RATE_WIDTH: usize = 12;
+
+/// `--once` 模式：采样 `duration` 后向 stdout 打印每个端口的汇总表
+///
+/// 不进入 TUI，也不重新扫描端口。Ctrl+C 提前结束时按已采样的部分汇总。
+pub async fn run(app: &mut App, duration: Duration) -> Result<()> {
+    let started = Instant::now();
+    tokio::select! {
+        _ = tokio::time::sleep(duration) => {},
+        _ = tokio::signal::ctrl_c() => {},
+    }
+
+    let histories: Vec<_> = app.histories.iter().filter_map(|h| h.read().ok()).collect();
+    let report = format_summary(&histories, app.unit, started.elapsed());
+    drop(histories);
+
+    let mut stdout = io::stdout().lock();
+    match stdout.write_all(report.as_bytes()).and_then(|_| stdout.flush()) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 汇总表：每个端口一行
+///
+/// 平均值由累计字节数除以该端口的监控时长得到，不受窗口聚合策略影响；
+/// 最小值与峰值取自缓冲区中的数据点 (`--once` 时缓冲区覆盖整个采样时长)。
+pub fn format_summary(histories: &[impl std::ops::Deref<Target = PortHistory>], unit: SpeedUnit, elapsed: Duration) -> String {
+    let name_width = histories.iter().map(|h| h.name.len()).max().unwrap_or(0).max("IFACE".len());
+    let mut out = String::new();
+    let _ = writeln!(out, "Summary over {:.1}s ({})", elapsed.as_secs_f64(), unit.label());
+    let _ = write!(out, "{:<name_width$}  {:<8}", "IFACE", "TYPE");
+    for column in ["RX AVG", "RX MIN", "RX PEAK", "TX AVG", "TX MIN", "TX PEAK", "RX TOTAL", "TX TOTAL"] {
+        let _ = write!(out, "  {:>RATE_WIDTH$}", column);
+    }
+    out.push('\n');
+
+    for history in histories {
+        let secs = history.started_at.elapsed().as_secs_f64();
+        let stats = history.stats();
+        let has_data = !history.rx_data.is_empty();
+        let _ = write!(out, "{:<name_width$}  {:<8}", history.name, history.port_type.as_str());
+        for (total, series) in [(history.total_rx_bytes, &stats.rx), (history.total_tx_bytes, &stats.tx)] {
+            let avg = if secs > 0.0 { total as f64 / secs } else { 0.0 };
+            let (min, peak) = if has_data {
+                (format_speed(series.min, unit), format_speed(series.max, unit))
+            } else {
+                ("-".to_string(), "-".to_string())
+            };
+            let _ = write!(out, "  {:>RATE_WIDTH$}  {:>RATE_WIDTH$}  {:>RATE_WIDTH$}", format_speed(avg, unit), min, peak);
+        }
+        let _ = writeln!(
+            out,
+            "  {:>RATE_WIDTH$}  {:>RATE_WIDTH$}",
+            format_bytes(history.total_rx_bytes),
+            format_bytes(history.total_tx_bytes)
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::PortType;
+    use crate::test_support::fake_history;
+
+    /// 按两个以上空格切分表格行，单元格内部 (如 "2.0 KB/s") 只有单个空格
+    fn cells(line: &str) -> Vec<&str> {
+        line.split("  ").map(str::trim).filter(|c| !c.is_empty()).collect()
+    }
+
+    #[test]
+    fn summary_table_reports_avg_min_peak_and_totals() {
+        let busy = fake_history("eth0", PortType::Ethernet, &[(1024.0, 0.0), (3072.0, 0.0)]);
+        let idle = fake_history("mlx5_0-1", PortType::Rdma, &[]);
+        for history in [&busy, &idle] {
+            history.write().unwrap().started_at = Instant::now() - Duration::from_secs(10);
+        }
+        busy.write().unwrap().total_rx_bytes = 20 * 1024;
+
+        let guards = [busy.read().unwrap(), idle.read().unwrap()];
+        let text = format_summary(&guards, SpeedUnit::Bytes, Duration::from_millis(10_040));
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "Summary over 10.0s (Bytes/s)");
+        assert_eq!(cells(lines[1]), [
+            "IFACE", "TYPE", "RX AVG", "RX MIN", "RX PEAK", "TX AVG", "TX MIN", "TX PEAK", "RX TOTAL", "TX TOTAL",
+        ]);
+        // 平均值 = 累计字节 / 监控时长，与缓冲区中的点无关
+        assert_eq!(cells(lines[2]), [
+            "eth0", "ethernet", "2.0 KB/s", "1.0 KB/s", "3.0 KB/s", "0 B/s", "0 B/s", "0 B/s", "20.00 KiB", "0 B",
+        ]);
+        // 尚无数据点的端口最小值与峰值显示为 "-"
+        assert_eq!(cells(lines[3]), ["mlx5_0-1", "rdma", "0 B/s", "-", "-", "0 B/s", "-", "-", "0 B", "0 B"]);
+        // 各列按表头对齐
+        assert!(lines[1..].iter().all(|l| l.len() == lines[1].len()));
+    }
+}
@@ -0,0 +1,50 @@
+//! 单元测试共用的夹具与辅助函数
+
+use crate::app::{App, SHUTDOWN_TIMEOUT};
+use crate::config::{Config, FileConfig};
+use crate::data::PortType;
+use crate::monitor::PortHistory;
+use crate::Args;
+use clap::Parser;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+#[path = "../tests/common/sysfs.rs"]
+mod sysfs;
+pub use sysfs::TempTree;
+
+/// 轮询直到条件成立或超时，返回条件最终是否成立
+pub fn wait_until(timeout: Duration, mut condition: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    condition()
+}
+
+/// 以 `tree` 为 sysfs 根解析命令行参数，不读取任何配置文件
+pub fn test_config(tree: &TempTree, args: &[&str]) -> Config {
+    let root = tree.path().to_str().unwrap();
+    let mut argv = vec!["rdma-dashboard-rust", "--sysfs-root", root];
+    argv.extend_from_slice(args);
+    Config::resolve(Args::parse_from(argv), FileConfig::default()).unwrap()
+}
+
+/// 在临时 sysfs 树上构造 App 并立即停止监控线程，测试可以直接改写 `histories`
+pub async fn test_app(tree: &TempTree, args: &[&str]) -> App {
+    let mut app = App::try_new(test_config(tree, args)).await.unwrap();
+    assert_eq!(app.shutdown_monitors(SHUTDOWN_TIMEOUT), 0);
+    app
+}
+
+/// 带有给定 (RX, TX) 序列的端口历史，时间轴按 1s 递增
+pub fn fake_history(name: &str, port_type: PortType, points: &[(f64, f64)]) -> Arc<RwLock<PortHistory>> {
+    let mut history = PortHistory::new(name.to_string(), port_type, 100);
+    for (i, &(rx, tx)) in points.iter().enumerate() {
+        history.push_point(i as f64, 0, rx, tx);
+    }
+    Arc::new(RwLock::new(history))
+}
@@ -0,0 +1,184 @@
+use crate::config::ColorOverrides;
+use crate::data::PortType;
+use ratatui::style::{Color, Style, Stylize};
+
+/// 内置配色方案
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeName {
+    /// Colors tuned for dark terminal backgrounds.
+    Dark,
+    /// Avoids yellow/cyan, which are hard to read on light backgrounds.
+    Light,
+    /// No colors; relies on glyphs, bold and reverse video only.
+    Mono,
+}
+
+impl ThemeName {
+    pub fn next(self) -> Self {
+        match self {
+            ThemeName::Dark => ThemeName::Light,
+            ThemeName::Light => ThemeName::Mono,
+            ThemeName::Mono => ThemeName::Dark,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeName::Dark => "Dark",
+            ThemeName::Light => "Light",
+            ThemeName::Mono => "Mono",
+        }
+    }
+
+    /// 未显式指定主题时的默认值：按 no-color.org 约定，`NO_COLOR` 非空即使用 mono
+    pub fn from_no_color(no_color: Option<&std::ffi::OsStr>) -> Self {
+        match no_color {
+            Some(v) if !v.is_empty() => ThemeName::Mono,
+            _ => ThemeName::Dark,
+        }
+    }
+}
+
+/// 单一端口类型的配色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortColors {
+    /// 面板边框与标题
+    pub border: Color,
+    /// 图表中的 RX / TX 曲线
+    pub rx: Color,
+    pub tx: Color,
+}
+
+/// UI 中用到的全部颜色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub rdma: PortColors,
+    pub ethernet: PortColors,
+    /// 表格、统计栏与状态栏中的 RX / TX 文字
+    pub rx: Color,
+    pub tx: Color,
+    /// 模式名、利用率文字、详情统计栏边框
+    pub accent: Color,
+    /// 状态栏中的按键提示
+    pub key: Color,
+    /// 错误计数、100% 线速参考线、退出键、高阈值告警
+    pub alert: Color,
+    /// 低阈值告警
+    pub warn: Color,
+    /// 坐标轴、次要说明文字、断线端口
+    pub muted: Color,
+    /// PAUSED 等醒目标记
+    pub badge: Style,
+}
+
+impl Theme {
+    pub fn preset(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Self {
+                rdma: PortColors { border: Color::Magenta, rx: Color::Magenta, tx: Color::Cyan },
+                ethernet: PortColors { border: Color::Green, rx: Color::Green, tx: Color::Yellow },
+                rx: Color::Green,
+                tx: Color::Magenta,
+                accent: Color::Cyan,
+                key: Color::Yellow,
+                alert: Color::Red,
+                warn: Color::Yellow,
+                muted: Color::DarkGray,
+                badge: Style::default().bold().fg(Color::Black).bg(Color::Red),
+            },
+            ThemeName::Light => Self {
+                rdma: PortColors { border: Color::Magenta, rx: Color::Magenta, tx: Color::Blue },
+                ethernet: PortColors { border: Color::Green, rx: Color::Green, tx: Color::Blue },
+                rx: Color::Green,
+                tx: Color::Magenta,
+                accent: Color::Blue,
+                key: Color::Blue,
+                alert: Color::Red,
+                warn: Color::Rgb(0xb5, 0x89, 0x00),
+                muted: Color::DarkGray,
+                badge: Style::default().bold().fg(Color::White).bg(Color::Red),
+            },
+            ThemeName::Mono => {
+                let plain = PortColors { border: Color::Reset, rx: Color::Reset, tx: Color::Reset };
+                Self {
+                    rdma: plain,
+                    ethernet: plain,
+                    rx: Color::Reset,
+                    tx: Color::Reset,
+                    accent: Color::Reset,
+                    key: Color::Reset,
+                    alert: Color::Reset,
+                    warn: Color::Reset,
+                    muted: Color::Reset,
+                    badge: Style::default().bold().reversed(),
+                }
+            }
+        }
+    }
+
+    /// 在预设之上叠加配置文件 `[colors]` 中的颜色
+    pub fn with_overrides(mut self, colors: &ColorOverrides) -> Self {
+        for (port, border) in [(&mut self.rdma, colors.rdma), (&mut self.ethernet, colors.ethernet)] {
+            port.border = border.unwrap_or(port.border);
+            port.rx = colors.rx.unwrap_or(port.rx);
+            port.tx = colors.tx.unwrap_or(port.tx);
+        }
+        self.rx = colors.rx.unwrap_or(self.rx);
+        self.tx = colors.tx.unwrap_or(self.tx);
+        self
+    }
+
+    pub fn port(&self, port_type: PortType) -> PortColors {
+        match port_type {
+            PortType::Rdma => self.rdma,
+            PortType::Ethernet => self.ethernet,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn presets_resolve_expected_colors() {
+        let dark = Theme::preset(ThemeName::Dark);
+        assert_eq!(dark.port(PortType::Rdma), PortColors { border: Color::Magenta, rx: Color::Magenta, tx: Color::Cyan });
+        assert_eq!(dark.port(PortType::Ethernet), PortColors { border: Color::Green, rx: Color::Green, tx: Color::Yellow });
+        assert_eq!(dark.key, Color::Yellow);
+
+        // 浅色背景下不使用黄色与青色
+        let light = Theme::preset(ThemeName::Light);
+        assert_eq!(light.rdma.tx, Color::Blue);
+        assert_eq!(light.ethernet.tx, Color::Blue);
+        assert_eq!(light.accent, Color::Blue);
+        assert_ne!(light.warn, Color::Yellow);
+
+        let mono = Theme::preset(ThemeName::Mono);
+        let colors = [mono.rdma.border, mono.rdma.rx, mono.ethernet.tx, mono.rx, mono.tx, mono.accent, mono.alert, mono.muted];
+        assert!(colors.iter().all(|&c| c == Color::Reset));
+        assert_eq!(mono.badge, Style::default().bold().reversed());
+    }
+
+    #[test]
+    fn no_color_selects_mono_only_when_non_empty() {
+        assert_eq!(ThemeName::from_no_color(None), ThemeName::Dark);
+        assert_eq!(ThemeName::from_no_color(Some(OsStr::new(""))), ThemeName::Dark);
+        assert_eq!(ThemeName::from_no_color(Some(OsStr::new("1"))), ThemeName::Mono);
+        assert_eq!(ThemeName::from_no_color(Some(OsStr::new("0"))), ThemeName::Mono);
+    }
+
+    #[test]
+    fn overrides_layer_on_top_of_preset() {
+        let colors = ColorOverrides { rdma: Some(Color::Red), rx: Some(Color::Rgb(1, 2, 3)), ..Default::default() };
+        let theme = Theme::preset(ThemeName::Light).with_overrides(&colors);
+        let preset = Theme::preset(ThemeName::Light);
+        assert_eq!(theme.rdma, PortColors { border: Color::Red, rx: Color::Rgb(1, 2, 3), tx: preset.rdma.tx });
+        assert_eq!(theme.ethernet, PortColors { rx: Color::Rgb(1, 2, 3), ..preset.ethernet });
+        assert_eq!((theme.rx, theme.tx), (Color::Rgb(1, 2, 3), preset.tx));
+        // 未覆盖的项保持预设值
+        assert_eq!(Theme { rdma: preset.rdma, ethernet: preset.ethernet, rx: preset.rx, ..theme }, preset);
+        assert_eq!(Theme::preset(ThemeName::Mono).with_overrides(&ColorOverrides::default()), Theme::preset(ThemeName::Mono));
+    }
+}
@@ -1,100 +1,340 @@
-use crate::app::{App, ViewMode};
-use crate::data::PortType;
+use crate::app::{AlertLevel, App, ViewMode};
+use crate::data::{LinkLayer, PortType};
+use crate::monitor::{PortHistory, SeriesStats};
 use ratatui::{
     prelude::*,
     symbols,
-    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph, Scrollbar, ScrollbarOrientation},
+    widgets::{Axis, Block, BorderType, Borders, Chart, Clear, Dataset, GraphType, Padding, Paragraph, Scrollbar, ScrollbarOrientation, Sparkline},
 };
+use std::time::Duration;
 
 // 定义每个图表占用的固定高度 (行数)
 // 12行比较合适，既能看清波形，一屏也能显示 3-4 个
 const CHART_HEIGHT: u16 = 12;
 
-pub fn render(app: &App, f: &mut Frame) {
+// 紧凑模式下每个端口只占两行 (RX / TX 各一条 sparkline)
+const SPARKLINE_HEIGHT: u16 = 2;
+// 紧凑模式左侧名称列与右侧数值列的宽度
+const SPARK_NAME_WIDTH: u16 = 26;
+const SPARK_VALUE_WIDTH: u16 = 12;
+
+// 详情视图右侧统计栏宽度
+const SIDEBAR_WIDTH: u16 = 34;
+
+// 低于这个尺寸时不绘制网格，只显示提示
+const MIN_WIDTH: u16 = 20;
+const MIN_HEIGHT: u16 = 8;
+
+// 带边框的面板至少要有一行内容，否则只剩边框
+const MIN_CELL_HEIGHT: u16 = 3;
+
+pub fn render(app: &mut App, f: &mut Frame) {
+    let area = f.area();
+    if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+        // 网格不可见，鼠标点击不应命中任何端口
+        app.grid_area = Rect::default();
+        render_too_small(f, area);
+        return;
+    }
+
     let main_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Min(0), Constraint::Length(1)])
         .split(f.area());
 
+    // 端口列表可能在运行时变化，先修正越界的下标
+    app.clamp_selection();
+    app.refresh_display_order();
+
     // 渲染主视图区域
-    render_scrollable_view(app, f, main_layout[0]);
+    match app.focused {
+        Some(index) => render_detail_view(app, f, main_layout[0], index),
+        None => {
+            app.grid_area = main_layout[0];
+            app.scroll_to_selected((main_layout[0].height / item_height(app.view_mode)) as usize);
+            render_scrollable_view(app, f, main_layout[0]);
+        }
+    }
 
     // 渲染底部状态栏
     render_footer(app, f, main_layout[1]);
+
+    // 帮助浮层最后绘制，覆盖在所有内容之上
+    if app.show_help {
+        render_help_popup(app, f, f.area());
+    }
+}
+
+/// 终端过小时居中显示的提示，按宽度折行
+fn render_too_small(f: &mut Frame, area: Rect) {
+    let message = format!("Terminal too small (need at least {}x{})", MIN_WIDTH, MIN_HEIGHT);
+    let lines = (message.len() as u16).div_ceil(area.width.max(1));
+    f.render_widget(
+        Paragraph::new(message)
+            .alignment(Alignment::Center)
+            .wrap(ratatui::widgets::Wrap { trim: true }),
+        centered_rect(area.width, lines, area),
+    );
+}
+
+/// 当前视图的名称，状态栏、帮助浮层与文本快照共用
+pub fn view_mode_label(app: &App) -> String {
+    match app.view_mode {
+        ViewMode::Table => "Table Mode".to_string(),
+        ViewMode::Chart => format!(
+            "Oscilloscope Mode ({} Precision)",
+            format_interval(app.monitor_config.sample_interval)
+        ),
+        ViewMode::Packets => "Packet Rate Mode".to_string(),
+        ViewMode::Errors => "Error Counters".to_string(),
+        ViewMode::Queues => "Per-Queue Mode".to_string(),
+        ViewMode::Sparkline => "Sparkline Mode".to_string(),
+    }
 }
 
 fn render_footer(app: &App, f: &mut Frame, area: Rect) {
-    let mode_str = match app.view_mode {
-        ViewMode::Table => "Table Mode",
-        ViewMode::Chart => "Oscilloscope Mode (1ms Precision)",
-    };
-    
-    let footer_text = Line::from(vec![
+    let mode_str = view_mode_label(app);
+
+    let mut spans = vec![
         Span::styled(format!(" RDMA Monitor v{} ", app.version), Style::default().bold()),
         Span::raw(" | "),
-        Span::styled(mode_str, Style::default().fg(Color::Cyan)),
+    ];
+    if let Some(range) = visible_range(app) {
+        spans.push(Span::styled(range, Style::default().fg(app.theme.muted)));
+        spans.push(Span::raw(" | "));
+    }
+    if app.is_paused() {
+        spans.push(Span::styled(" PAUSED ", app.theme.badge));
+        spans.push(Span::raw(" | "));
+    }
+    if let Some(notice) = app.active_notice() {
+        spans.push(Span::styled(notice.to_string(), Style::default().bold().fg(app.theme.accent)));
+        spans.push(Span::raw(" | "));
+    }
+    let alerts = app.alert_count();
+    if alerts > 0 {
+        spans.push(Span::styled(format!("⚠ {} ALERT ", alerts), Style::default().bold().fg(app.theme.alert)));
+        spans.push(Span::raw(" | "));
+    }
+    // 状态放在前面、按键提示放在最后：行超出终端宽度时 Paragraph 保留左侧，
+    // 其余快捷键只在 `?` 浮层中列出
+    let (total_rx, total_tx) = app.aggregate_latest();
+    spans.extend([
+        Span::styled("u", Style::default().bold().fg(app.theme.key)),
+        Span::raw(format!(" Units: {} | ", app.unit.label())),
+        Span::styled("a", Style::default().bold().fg(app.theme.key)),
+        Span::raw(format!(" Agg: {} | ", app.aggregation().label())),
+        Span::styled("s", Style::default().bold().fg(app.theme.key)),
+        Span::raw(format!(" Sort: {} | ", app.sort_mode.label())),
+        Span::styled(mode_str, Style::default().fg(app.theme.accent)),
+        Span::styled(format!(" @ {} FPS", app.fps), Style::default().fg(app.theme.muted)),
         Span::raw(" | "),
-        Span::styled("↑/↓/j/k", Style::default().bold().fg(Color::Yellow)),
-        Span::raw(" Scroll | "),
-        Span::styled("Tab", Style::default().bold().fg(Color::Yellow)),
-        Span::raw(" Switch View | "),
-        Span::styled("q", Style::default().bold().fg(Color::Red)),
+        Span::styled("Σ ", Style::default().bold()),
+        Span::styled(format!("RX {} ", format_speed(total_rx, app.unit)), Style::default().fg(app.theme.rx)),
+        Span::styled(format!("TX {}", format_speed(total_tx, app.unit)), Style::default().fg(app.theme.tx)),
+        Span::raw(" | "),
+        Span::styled("t", Style::default().bold().fg(app.theme.key)),
+        Span::raw(format!(" Theme: {} | ", app.theme_name.label())),
+        Span::styled("?", Style::default().bold().fg(app.theme.key)),
+        Span::raw(" Help | "),
+        Span::styled("q", Style::default().bold().fg(app.theme.alert)),
         Span::raw(" Quit"),
     ]);
-    
+    let footer_text = Line::from(spans);
+
     f.render_widget(
         Paragraph::new(footer_text).alignment(Alignment::Center),
         area,
     );
 }
 
-/// 统一的可滚动视图渲染逻辑
-fn render_scrollable_view(app: &App, f: &mut Frame, area: Rect) {
-    let total_items = app.histories.len();
-    if total_items == 0 { return; }
+// 帮助浮层中按键列的宽度
+const HELP_KEY_WIDTH: usize = 14;
 
-    // 1. 计算当前屏幕能放下多少个图表
-    let items_per_screen = (area.height / CHART_HEIGHT) as usize;
-    // 确保至少渲染 1 个
-    let num_render = if items_per_screen == 0 { 1 } else { items_per_screen + 1 }; 
+/// 帮助浮层中列出的按键与说明，与 handler.rs 中的绑定保持一致
+const HELP_KEYS: &[(&str, &str)] = &[
+    ("Tab", "Switch view"),
+    ("↑/↓ j/k", "Select port / scroll"),
+    ("Enter", "Open / close detail view"),
+    ("Esc", "Close detail view, or quit"),
+    ("Space / p", "Pause / resume"),
+    ("c", "Clear history"),
+    ("u", "Toggle Bytes/s and bits/s"),
+    ("l", "Toggle link utilization"),
+    ("a", "Cycle aggregation"),
+    ("t", "Cycle theme"),
+    ("s", "Cycle sort (name / RX / TX / total)"),
+    ("S", "Save a text snapshot to ./rdma-dashboard-<time>.txt"),
+    ("Mouse", "Click to select, wheel to scroll"),
+    ("? / h", "Toggle this help"),
+    ("q / Ctrl+C", "Quit"),
+];
+
+/// 以 `area` 为界居中放置一个 `width` x `height` 的矩形；终端过小时缩到 `area` 以内
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// 快捷键与当前设置的居中浮层，背景整体变暗
+fn render_help_popup(app: &App, f: &mut Frame, area: Rect) {
+    let key_style = Style::default().bold().fg(app.theme.key);
+    let label_style = Style::default().fg(app.theme.muted);
+    let config = &app.monitor_config;
+
+    let mut lines: Vec<Line> = HELP_KEYS
+        .iter()
+        .map(|(key, desc)| Line::from(vec![
+            Span::styled(format!("{:<width$}", key, width = HELP_KEY_WIDTH), key_style),
+            Span::raw(*desc),
+        ]))
+        .collect();
+    lines.push(Line::raw(""));
+
+    let settings = [
+        ("View", view_mode_label(app)),
+        ("Units", app.unit.label().to_string()),
+        ("Aggregation", app.aggregation().label().to_string()),
+        ("Theme", app.theme_name.label().to_string()),
+        ("Sort", app.sort_mode.label().to_string()),
+        ("Sampling", format_interval(config.sample_interval)),
+        ("Chart point", format_interval(config.commit_interval)),
+        ("History", format!(
+            "{} points ({:.1}s)",
+            config.history_points,
+            config.commit_interval.as_secs_f64() * config.history_points as f64
+        )),
+        ("Refresh", format!("{} FPS", app.fps)),
+        ("Ports", format!("{} ({})", app.histories.len(), app.type_filter.as_str())),
+    ];
+    lines.extend(settings.into_iter().map(|(label, value)| Line::from(vec![
+        Span::styled(format!("{:<width$}", label, width = HELP_KEY_WIDTH), label_style),
+        Span::raw(value),
+    ])));
 
-    // 2. 计算当前可见的 items 范围
-    let start_idx = app.vertical_scroll;
-    let end_idx = (start_idx + num_render).min(total_items);
+    // 内容宽度 + 边框与左右各一格留白
+    let content_width = lines.iter().map(Line::width).max().unwrap_or(0) as u16;
+    let popup = centered_rect(content_width + 4, lines.len() as u16 + 2, area);
 
-    // 3. 为可见的 items 创建布局区域
-    // 我们手动计算每个 item 的 Rect
+    f.buffer_mut().set_style(area, Style::default().add_modifier(Modifier::DIM));
+    f.render_widget(Clear, popup);
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(app.theme.accent))
+                .title(" Help (? / Esc to close) ")
+                .padding(Padding::horizontal(1)),
+        ),
+        popup,
+    );
+}
+
+/// 各视图中单个端口面板的高度；紧凑模式用更密的布局，一屏可放下更多端口
+pub fn item_height(view_mode: ViewMode) -> u16 {
+    match view_mode {
+        ViewMode::Sparkline => SPARKLINE_HEIGHT,
+        _ => CHART_HEIGHT,
+    }
+}
+
+/// 计算网格中各可见面板的区域
+///
+/// 从 `start` 开始自上而下排列，每个面板高 `item_height`，最后一个可能被截断；
+/// 右侧留 1 列给滚动条。鼠标点击的命中测试复用同一套计算。
+///
+/// 截断后放不下一行内容的面板直接省略，保证不产生零面积或只剩边框的区域。
+pub fn layout_grid(area: Rect, start: usize, total: usize, item_height: u16) -> Vec<(usize, Rect)> {
+    let mut cells = Vec::new();
+    let bottom = area.y + area.height;
+    let width = area.width.saturating_sub(1);
+    let min_height = item_height.clamp(1, MIN_CELL_HEIGHT);
     let mut current_y = area.y;
-    
-    for i in start_idx..end_idx {
-        // 防止超出屏幕底部
-        if current_y >= area.y + area.height {
+    if width == 0 {
+        return cells;
+    }
+
+    for i in start..total {
+        let height = bottom.saturating_sub(current_y).min(item_height);
+        if height < min_height {
             break;
         }
-
-        // 计算当前 item 的高度（处理最后一个可能被截断的情况）
-        let remaining_height = (area.y + area.height).saturating_sub(current_y);
-        let height = remaining_height.min(CHART_HEIGHT);
-        
-        if height == 0 { break; }
-
-        let item_area = Rect {
+        cells.push((i, Rect {
             x: area.x,
             y: current_y,
-            width: area.width - 1, // 留 1 列给滚动条
+            width,
             height,
-        };
+        }));
+        current_y += height;
+    }
+    cells
+}
+
+/// 网格放不下全部端口时的提示，例如 "showing 1-6 of 16"；详情视图下不显示
+fn visible_range(app: &App) -> Option<String> {
+    if app.focused.is_some() {
+        return None;
+    }
+    let total = app.histories.len();
+    let shown = layout_grid(app.grid_area, app.vertical_scroll, total, item_height(app.view_mode)).len();
+    (shown < total).then(|| {
+        let first = (app.vertical_scroll + 1).min(total);
+        format!("showing {}-{} of {}", first, app.vertical_scroll + shown, total)
+    })
+}
 
-        // 渲染单个 item
+/// 屏幕坐标 -> 网格中的端口下标；详情视图下不命中任何面板
+pub fn port_at(app: &App, column: u16, row: u16) -> Option<usize> {
+    if app.focused.is_some() {
+        return None;
+    }
+    let position = Position::new(column, row);
+    layout_grid(app.grid_area, app.vertical_scroll, app.histories.len(), item_height(app.view_mode))
+        .into_iter()
+        .find(|(_, rect)| rect.contains(position))
+        .and_then(|(slot, _)| app.display_order.get(slot).copied())
+}
+
+/// 统一的可滚动视图渲染逻辑
+fn render_scrollable_view(app: &App, f: &mut Frame, area: Rect) {
+    let total_items = app.histories.len();
+    if total_items == 0 {
+        // 点名的端口都还没出现，重新扫描发现后自动显示
+        let waiting = app.waiting_for();
+        if !waiting.is_empty() {
+            let message = Paragraph::new(format!("Waiting for {} to appear…", waiting.join(", ")))
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(app.theme.muted));
+            f.render_widget(message, centered_rect(area.width, 1, area));
+        }
+        return;
+    }
+
+    // 1. 计算当前屏幕能放下多少个图表
+    let height = item_height(app.view_mode);
+    let items_per_screen = (area.height / height) as usize;
+
+    // 2. 从滚动偏移开始排布可见的 items，并逐个渲染
+    for (slot, item_area) in layout_grid(area, app.vertical_scroll, total_items, height) {
+        let Some(&i) = app.display_order.get(slot) else { continue };
         match app.view_mode {
             ViewMode::Table => render_single_table_item(app, f, item_area, i),
-            ViewMode::Chart => render_single_chart_item(app, f, item_area, i),
+            ViewMode::Chart => render_single_chart_item(app, f, item_area, i, ChartSeries::Bytes),
+            ViewMode::Packets => render_single_chart_item(app, f, item_area, i, ChartSeries::Packets),
+            ViewMode::Errors => render_single_error_item(app, f, item_area, i),
+            ViewMode::Queues => render_single_queue_item(app, f, item_area, i),
+            ViewMode::Sparkline => render_single_sparkline_item(app, f, item_area, i),
         }
-
-        current_y += height;
     }
 
-    // 4. 渲染滚动条
+    // 3. 渲染滚动条
     let scrollbar = Scrollbar::default()
         .orientation(ScrollbarOrientation::VerticalRight)
         .begin_symbol(Some("↑"))
@@ -112,95 +352,596 @@ fn render_scrollable_view(app: &App, f: &mut Frame, area: Rect) {
     );
 }
 
+/// 图表绘制的数据序列
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ChartSeries {
+    Bytes,
+    Packets,
+}
+
 // 渲染单个图表项
-fn render_single_chart_item(app: &App, f: &mut Frame, area: Rect, index: usize) {
-    if let Some(history_lock) = app.histories.get(index) {
-        if let Ok(history) = history_lock.read() {
-            // 数据准备
-            let rx_data: Vec<(f64, f64)> = history.rx_data.iter().cloned().collect();
-            let tx_data: Vec<(f64, f64)> = history.tx_data.iter().cloned().collect();
-
-            // 颜色
-            let (rx_color, tx_color, title_prefix, border_color) = match history.port_type {
-                PortType::Rdma => (Color::Magenta, Color::Cyan, "[RDMA]", Color::Magenta),
-                PortType::Ethernet => (Color::Green, Color::Yellow, "[ETH] ", Color::Green),
-            };
-
-            // Y轴范围
-            let max_val = rx_data.iter().chain(tx_data.iter())
-                .map(|(_, v)| *v).fold(0.0, f64::max);
-            let y_upper = if max_val <= 1024.0 { 1024.0 } else { max_val * 1.1 };
-            
-            // X轴范围
-            let min_x = rx_data.first().map(|(t, _)| *t).unwrap_or(0.0);
-            let max_x = rx_data.last().map(|(t, _)| *t).unwrap_or(10.0);
-
-            // 绘图
-            let datasets = vec![
-                Dataset::default().name("RX").marker(symbols::Marker::Braille)
-                    .graph_type(GraphType::Line).style(Style::default().fg(rx_color)).data(&rx_data),
-                Dataset::default().name("TX").marker(symbols::Marker::Braille)
-                    .graph_type(GraphType::Line).style(Style::default().fg(tx_color)).data(&tx_data),
-            ];
-
-            let chart = Chart::new(datasets)
-                .block(Block::default()
-                    .title(format!("{} {}", title_prefix, history.name))
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(border_color)))
-                .x_axis(Axis::default().style(Style::default().fg(Color::DarkGray)).bounds([min_x, max_x])
-                    .labels(vec![Span::raw(format!("{:.1}", min_x)), Span::raw(format!("{:.1}", max_x))]))
-                .y_axis(Axis::default().style(Style::default().fg(Color::DarkGray)).bounds([0.0, y_upper])
-                    .labels(vec![Span::raw("0"), Span::styled(format_speed(y_upper), Style::default().bold())]));
-
-            f.render_widget(chart, area);
+fn render_single_chart_item(app: &App, f: &mut Frame, area: Rect, index: usize, series: ChartSeries) {
+    if let Some(history_lock) = app.histories.get(index)
+        && let Ok(history) = history_lock.read() {
+        // 数据准备
+        let (rx_src, tx_src) = match series {
+            ChartSeries::Bytes => (&history.rx_data, &history.tx_data),
+            ChartSeries::Packets => (&history.rx_pps, &history.tx_pps),
+        };
+        let rx_data: Vec<(f64, f64)> = rx_src.iter().cloned().collect();
+        let tx_data: Vec<(f64, f64)> = tx_src.iter().cloned().collect();
+
+        // 颜色
+        let (title_prefix, border_color, rx_color, tx_color) = port_style(app, &history);
+
+        // Y轴范围
+        let max_val = rx_data.iter().chain(tx_data.iter())
+            .map(|(_, v)| *v).fold(0.0, f64::max);
+        let y_floor = match series {
+            ChartSeries::Bytes => 1024.0,
+            ChartSeries::Packets => 10.0,
+        };
+        let auto_upper = if max_val <= y_floor { y_floor } else { max_val * 1.1 };
+
+        // 链路利用率叠加：仅对字节序列生效，链路速率未知时退回自动缩放
+        let overlay = series == ChartSeries::Bytes && app.show_utilization;
+        let link_bytes = history.link_rate_bps.filter(|_| overlay).map(|bps| bps / 8.0);
+        let y_upper = match link_bytes {
+            Some(link) => auto_upper.max(link * 1.05),
+            None => auto_upper,
+        };
+        let y_label = match series {
+            ChartSeries::Bytes => format_speed(y_upper, app.unit),
+            ChartSeries::Packets => format_pps(y_upper),
+        };
+
+        let mut title = port_title(title_prefix, &history);
+        if series == ChartSeries::Packets && !history.has_pps {
+            title.push_str(" (packet counters unavailable)");
         }
+        if overlay {
+            title.push_str(&utilization_summary(&history));
+        }
+        
+        // X轴范围
+        let min_x = rx_data.first().map(|(t, _)| *t).unwrap_or(0.0);
+        let max_x = rx_data.last().map(|(t, _)| *t).unwrap_or(10.0);
+
+        // 100% 线速参考线
+        let line_rate_data: Vec<(f64, f64)> = link_bytes
+            .map(|link| vec![(min_x, link), (max_x, link)])
+            .unwrap_or_default();
+
+        // 绘图
+        let mut datasets = vec![
+            Dataset::default().name("RX").marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line).style(Style::default().fg(rx_color)).data(&rx_data),
+            Dataset::default().name("TX").marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line).style(Style::default().fg(tx_color)).data(&tx_data),
+        ];
+        if !line_rate_data.is_empty() {
+            datasets.push(Dataset::default().marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line).style(Style::default().fg(app.theme.alert)).data(&line_rate_data));
+        }
+
+        let chart = Chart::new(datasets)
+            .block(port_block(app, index, &history, title, border_color))
+            .x_axis(Axis::default().style(Style::default().fg(app.theme.muted)).bounds([min_x, max_x])
+                .labels(vec![
+                    Span::raw(format_clock(history.timestamps.front().copied())),
+                    Span::raw(format_clock(history.timestamps.back().copied())),
+                ]))
+            .y_axis(Axis::default().style(Style::default().fg(app.theme.muted)).bounds([0.0, y_upper])
+                .labels(vec![Span::raw("0"), Span::styled(y_label, Style::default().bold())]));
+
+        f.render_widget(chart, area);
     }
 }
 
 // 渲染单个表格项
 fn render_single_table_item(app: &App, f: &mut Frame, area: Rect, index: usize) {
-    if let Some(history_lock) = app.histories.get(index) {
-        if let Ok(history) = history_lock.read() {
-            let (last_rx, last_tx) = match (history.rx_data.back(), history.tx_data.back()) {
-                (Some((_, rx)), Some((_, tx))) => (*rx, *tx),
-                _ => (0.0, 0.0),
-            };
-
-            let (type_str, title_color) = match history.port_type {
-                PortType::Rdma => ("[RDMA]", Color::Magenta),
-                PortType::Ethernet => ("[ETH] ", Color::Green),
-            };
-
-            let text = vec![
-                Line::from(vec![
-                    Span::styled("RX Speed: ", Style::default().fg(Color::Green)),
-                    Span::styled(format_speed(last_rx), Style::default().bold()),
-                ]),
-                Line::from(vec![
-                    Span::styled("TX Speed: ", Style::default().fg(Color::Magenta)),
-                    Span::styled(format_speed(last_tx), Style::default().bold()),
-                ]),
-            ];
-
-            // 表格模式下，不需要那么高，可以在内部居中
-            let block = Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(title_color))
-                .title(Span::styled(format!("{} {}", type_str, history.name), Style::default().bold()));
+    if let Some(history_lock) = app.histories.get(index)
+        && let Ok(history) = history_lock.read() {
+        let (last_rx, last_tx) = match (history.rx_data.back(), history.tx_data.back()) {
+            (Some((_, rx)), Some((_, tx))) => (*rx, *tx),
+            _ => (0.0, 0.0),
+        };
+
+        let (type_str, title_color, _, _) = port_style(app, &history);
+
+        // 瞬时值抖动较大，每个方向下方附上缓冲窗口内的统计
+        let stats = history.stats();
+        let mut text = vec![
+            Line::from(vec![
+                Span::styled("RX Speed: ", Style::default().fg(app.theme.rx)),
+                Span::styled(format_speed(last_rx, app.unit), Style::default().bold()),
+            ]),
+            window_stats_line(app, &stats.rx),
+            Line::from(vec![
+                Span::styled("TX Speed: ", Style::default().fg(app.theme.tx)),
+                Span::styled(format_speed(last_tx, app.unit), Style::default().bold()),
+            ]),
+            window_stats_line(app, &stats.tx),
+        ];
+
+        if app.show_utilization {
+            text.push(Line::from(Span::styled(
+                utilization_summary(&history).trim_start().to_string(),
+                Style::default().fg(app.theme.accent),
+            )));
+        }
+
+        if history.has_pps {
+            let last_rx_pps = history.rx_pps.back().map(|(_, v)| *v).unwrap_or(0.0);
+            let last_tx_pps = history.tx_pps.back().map(|(_, v)| *v).unwrap_or(0.0);
+            text.push(Line::from(vec![
+                Span::styled("RX Pkts:  ", Style::default().fg(app.theme.rx)),
+                Span::raw(format_pps(last_rx_pps)),
+            ]));
+            text.push(Line::from(vec![
+                Span::styled("TX Pkts:  ", Style::default().fg(app.theme.tx)),
+                Span::raw(format_pps(last_tx_pps)),
+            ]));
+        }
+
+        // 监控启动以来的累计流量，不受清空历史影响
+        text.push(Line::from(vec![
+            Span::raw("Total:    "),
+            Span::styled(format!("RX {}", format_bytes(history.total_rx_bytes)), Style::default().fg(app.theme.rx)),
+            Span::raw("  "),
+            Span::styled(format!("TX {}", format_bytes(history.total_tx_bytes)), Style::default().fg(app.theme.tx)),
+        ]));
+
+        // 表格模式下，不需要那么高，可以在内部居中
+        let block = port_block(app, index, &history, port_title(type_str, &history), title_color);
+
+        f.render_widget(
+            Paragraph::new(text)
+                .block(block)
+                .alignment(Alignment::Left) // 表格模式下左对齐看起来更像列表
+                .wrap(ratatui::widgets::Wrap { trim: false }), // 保留统计行的缩进
+            area
+        );
+    }
+}
+
+/// 表格模式中的窗口统计行，例如 "  avg 2.8 GB/s  min 1.0 GB/s  max 4.1 GB/s"
+fn window_stats_line(app: &App, stats: &SeriesStats) -> Line<'static> {
+    Line::from(Span::styled(
+        format!(
+            "  avg {}  min {}  max {}",
+            format_speed(stats.avg, app.unit),
+            format_speed(stats.min, app.unit),
+            format_speed(stats.max, app.unit)
+        ),
+        Style::default().fg(app.theme.muted),
+    ))
+}
+
+pub fn format_pps(pps: f64) -> String {
+    if pps < 1000.0 { return format!("{:.0} pps", pps); }
+    let kpps = pps / 1000.0;
+    if kpps < 1000.0 { return format!("{:.1} Kpps", kpps); }
+    format!("{:.2} Mpps", kpps / 1000.0)
+}
+
+fn format_interval(d: Duration) -> String {
+    let us = d.as_micros();
+    if !us.is_multiple_of(1000) { format!("{}us", us) } else { format!("{}ms", us / 1000) }
+}
+
+/// 端口标题前缀，补齐到同一宽度
+///
+/// RDMA 端口按链路层细分为 IB / RoCE；读不到 `link_layer` 时仍显示 RDMA。
+pub fn port_label(history: &PortHistory) -> &'static str {
+    match (history.port_type, history.link_layer) {
+        (PortType::Rdma, Some(LinkLayer::InfiniBand)) => "[IB]  ",
+        (PortType::Rdma, Some(LinkLayer::Roce)) => "[RoCE]",
+        (PortType::Rdma, None) => "[RDMA]",
+        (PortType::Ethernet, _) => "[ETH] ",
+    }
+}
+
+/// 端口类型对应的 (标题前缀, 边框色, RX 曲线色, TX 曲线色)
+fn port_style(app: &App, history: &PortHistory) -> (&'static str, Color, Color, Color) {
+    let colors = app.theme.port(history.port_type);
+    (port_label(history), colors.border, colors.rx, colors.tx)
+}
+
+pub const STALE_SUFFIX: &str = " [DISCONNECTED]";
+
+/// 端口面板标题；断线重连中的端口追加标记
+fn port_title(type_str: &str, history: &PortHistory) -> String {
+    if history.stale {
+        format!("{} {}{}", type_str, history.name, STALE_SUFFIX)
+    } else {
+        format!("{} {}", type_str, history.name)
+    }
+}
+
+/// 断线重连中的端口统一置灰
+fn stale_aware_style(app: &App, history: &PortHistory, color: Color) -> Style {
+    if history.stale {
+        Style::default().fg(app.theme.muted)
+    } else {
+        Style::default().fg(color)
+    }
+}
+
+// 告警边框的闪烁半周期
+const ALERT_BLINK: Duration = Duration::from_millis(500);
+
+fn blink_on() -> bool {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    (now.as_millis() / ALERT_BLINK.as_millis()).is_multiple_of(2)
+}
+
+/// 告警端口：标题加 ⚠，颜色按 ALERT_BLINK 在告警色与原色之间闪烁
+fn alert_decoration(app: &App, history: &PortHistory, title: String, color: Color) -> (String, Color) {
+    match app.alert_state(history) {
+        AlertLevel::Normal => (title, color),
+        level => {
+            let alert_color = if level == AlertLevel::High { app.theme.alert } else { app.theme.warn };
+            (format!("⚠ {}", title), if blink_on() { alert_color } else { color })
+        }
+    }
+}
+
+/// 端口面板外框：断线置灰，网格中被光标选中的面板使用粗边框
+fn port_block(app: &App, index: usize, history: &PortHistory, title: String, color: Color) -> Block<'static> {
+    let (title, color) = alert_decoration(app, history, title, color);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(stale_aware_style(app, history, color))
+        .title(Span::styled(title, Style::default().bold()));
+    if app.focused.is_none() && app.selected == index {
+        block.border_type(BorderType::Thick)
+    } else {
+        block
+    }
+}
+
+/// 单端口全屏详情：左侧大图表，右侧统计栏
+fn render_detail_view(app: &App, f: &mut Frame, area: Rect, index: usize) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(SIDEBAR_WIDTH)])
+        .split(area);
+
+    let series = if app.view_mode == ViewMode::Packets { ChartSeries::Packets } else { ChartSeries::Bytes };
+    render_single_chart_item(app, f, columns[0], index, series);
+    render_detail_sidebar(app, f, columns[1], index);
+}
+
+fn render_detail_sidebar(app: &App, f: &mut Frame, area: Rect, index: usize) {
+    let Some(history_lock) = app.histories.get(index) else { return };
+    let Ok(history) = history_lock.read() else { return };
+
+    let stats = history.stats();
+    let mut text = Vec::new();
+    for (label, series, color) in [("RX", stats.rx, app.theme.rx), ("TX", stats.tx, app.theme.tx)] {
+        text.push(Line::from(Span::styled(label, Style::default().bold().fg(color))));
+        for (name, value) in [
+            ("Current", series.current),
+            ("Min", series.min),
+            ("Max", series.max),
+            ("Avg", series.avg),
+        ] {
+            text.push(Line::from(vec![
+                Span::raw(format!("  {:<9}", name)),
+                Span::styled(format_speed(value, app.unit), Style::default().bold()),
+            ]));
+        }
+        text.push(Line::from(""));
+    }
+
+    let window = app.monitor_config.commit_interval.as_secs_f64() * history.rx_data.len() as f64;
+    let link = history
+        .link_rate_bps
+        .map(|bps| format_speed(bps / 8.0, SpeedUnit::Bits))
+        .unwrap_or_else(|| "unknown".to_string());
+    for (name, value) in [
+        ("Total RX", format_bytes(history.total_rx_bytes)),
+        ("Total TX", format_bytes(history.total_tx_bytes)),
+        ("Uptime", format_duration(history.started_at.elapsed())),
+        ("Window", format!("{:.1}s ({} pts)", window, history.rx_data.len())),
+        ("Link", link),
+    ] {
+        text.push(Line::from(vec![
+            Span::styled(format!("{:<11}", name), Style::default().fg(app.theme.muted)),
+            Span::raw(value),
+        ]));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(stale_aware_style(app, &history, app.theme.accent))
+        .title(Span::styled(" Stats (Esc/Enter back) ", Style::default().bold()));
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+/// 累计字节数，1024 进制 (KiB/MiB/GiB/TiB)
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} B", bytes) } else { format!("{:.2} {}", value, UNITS[unit]) }
+}
+
+/// Unix 毫秒时间戳格式化为本地时间 HH:MM:SS；没有数据时显示占位符
+fn format_clock(timestamp_ms: Option<u64>) -> String {
+    timestamp_ms
+        .and_then(|ms| chrono::DateTime::from_timestamp_millis(ms as i64))
+        .map(|t| t.with_timezone(&chrono::Local).format("%H:%M:%S").to_string())
+        .unwrap_or_else(|| "--:--:--".to_string())
+}
+
+/// 时长格式化为 HH:MM:SS
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60)
+}
+
+/// 相对链路速率的利用率 (百分比)
+pub fn utilization_percent(bytes_per_sec: f64, link_rate_bps: f64) -> f64 {
+    bytes_per_sec * 8.0 / link_rate_bps * 100.0
+}
+
+/// 利用率摘要文本，例如 " RX 45.2% TX 3.1% of 100.0 Gb/s"
+fn utilization_summary(history: &PortHistory) -> String {
+    let Some(link) = history.link_rate_bps else {
+        return " (link rate unknown)".to_string();
+    };
+    let last_rx = history.rx_data.back().map(|(_, v)| *v).unwrap_or(0.0);
+    let last_tx = history.tx_data.back().map(|(_, v)| *v).unwrap_or(0.0);
+    format!(
+        " RX {:.1}% TX {:.1}% of {}",
+        utilization_percent(last_rx, link),
+        utilization_percent(last_tx, link),
+        format_speed(link / 8.0, SpeedUnit::Bits)
+    )
+}
+
+/// 速率显示单位
+/// 字节模式使用 1024 进制 (KB/s)，比特模式按网卡规格惯例使用 1000 进制 (Gb/s)
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpeedUnit {
+    Bytes,
+    Bits,
+}
+
+impl SpeedUnit {
+    pub fn toggle(self) -> Self {
+        match self {
+            SpeedUnit::Bytes => SpeedUnit::Bits,
+            SpeedUnit::Bits => SpeedUnit::Bytes,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SpeedUnit::Bytes => "Bytes/s",
+            SpeedUnit::Bits => "Bits/s",
+        }
+    }
+}
+
+/// `format_speed` 的逆操作：解析 "80Gb/s"、"500 MB/s"、"1.5G" 等写法为 Bytes/s
+///
+/// 前缀 K/M/G/T 在比特单位下按 1000 进制、字节单位下按 1024 进制；
+/// 省略单位 (`b/s`、`B/s` 等) 时按 `default_unit` 解释。
+pub fn parse_speed(text: &str, default_unit: SpeedUnit) -> Result<f64, String> {
+    let text = text.trim();
+    let split = text.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(text.len());
+    let (number, suffix) = text.split_at(split);
+    let value: f64 = number.parse().map_err(|_| "expected a number".to_string())?;
+    let suffix = suffix.trim_start();
+
+    let (exponent, unit_part) = match suffix.chars().next().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (1, &suffix[1..]),
+        Some('M') => (2, &suffix[1..]),
+        Some('G') => (3, &suffix[1..]),
+        Some('T') => (4, &suffix[1..]),
+        _ => (0, suffix),
+    };
+    let unit = match unit_part {
+        "" => default_unit,
+        "b" | "b/s" | "bps" | "bit" | "bits" => SpeedUnit::Bits,
+        "B" | "B/s" | "Bps" => SpeedUnit::Bytes,
+        other => return Err(format!("unknown unit `{}`", other)),
+    };
+    Ok(match unit {
+        SpeedUnit::Bits => value * 1000f64.powi(exponent) / 8.0,
+        SpeedUnit::Bytes => value * 1024f64.powi(exponent),
+    })
+}
+
+pub fn format_speed(bytes_per_sec: f64, unit: SpeedUnit) -> String {
+    match unit {
+        SpeedUnit::Bytes => format_bytes_per_sec(bytes_per_sec),
+        SpeedUnit::Bits => format_bits_per_sec(bytes_per_sec * 8.0),
+    }
+}
+
+// 渲染单个错误计数器面板：累计值、最近增量以及增量时间线 (非零为红色)
+fn render_single_error_item(app: &App, f: &mut Frame, area: Rect, index: usize) {
+    if let Some(history_lock) = app.histories.get(index)
+        && let Ok(history) = history_lock.read() {
+        let (type_str, title_color, _, _) = port_style(app, &history);
+
+        const NAME_WIDTH: usize = 20;
+        const STATS_WIDTH: usize = 28;
+        // 时间线可用宽度 = 内部宽度 - 名称列 - 统计列
+        let timeline_width = (area.width as usize)
+            .saturating_sub(2 + NAME_WIDTH + STATS_WIDTH);
+
+        let text: Vec<Line> = if history.errors.is_empty() {
+            vec![Line::from(Span::styled(
+                "No error counters available on this port",
+                Style::default().fg(app.theme.muted),
+            ))]
+        } else {
+            history.errors.iter().map(|counter| {
+                let last = counter.last_delta();
+                let name_style = if last > 0 {
+                    Style::default().fg(app.theme.alert).bold()
+                } else {
+                    Style::default()
+                };
+
+                let mut spans = vec![
+                    Span::styled(format!("{:<w$}", counter.name, w = NAME_WIDTH), name_style),
+                    Span::raw(format!(
+                        "{:<w$}",
+                        format!("total {:>8}  +{:<6}", counter.total, last),
+                        w = STATS_WIDTH
+                    )),
+                ];
+
+                // 增量时间线：每个字符代表一个轮询周期，最新的在右侧
+                let skip = counter.deltas.len().saturating_sub(timeline_width);
+                for &delta in counter.deltas.iter().skip(skip) {
+                    spans.push(if delta > 0 {
+                        Span::styled("█", Style::default().fg(app.theme.alert))
+                    } else {
+                        Span::styled("·", Style::default().fg(app.theme.muted))
+                    });
+                }
+                Line::from(spans)
+            }).collect()
+        };
+
+        let block = port_block(app, index, &history, port_title(type_str, &history), title_color);
 
+        f.render_widget(Paragraph::new(text).block(block), area);
+    }
+}
+
+// 渲染单个端口的每队列速率；队列较多时按列排布，放不下的截断
+fn render_single_queue_item(app: &App, f: &mut Frame, area: Rect, index: usize) {
+    if let Some(history_lock) = app.histories.get(index)
+        && let Ok(history) = history_lock.read() {
+        let (type_str, title_color, _, _) = port_style(app, &history);
+
+        const NAME_WIDTH: usize = 7;
+        const SPEED_WIDTH: usize = 12;
+        // 名称 + "RX " + 速率 + " TX " + 速率 + 列间距
+        const CELL_WIDTH: usize = NAME_WIDTH + 3 + SPEED_WIDTH + 4 + SPEED_WIDTH + 3;
+
+        let text: Vec<Line> = if history.port_type != PortType::Ethernet {
+            vec![Line::from(Span::styled(
+                "Per-queue counters are only collected for Ethernet ports",
+                Style::default().fg(app.theme.muted),
+            ))]
+        } else if let Some(error) = &history.queue_error {
+            vec![Line::from(Span::styled(error.as_str(), Style::default().fg(app.theme.alert)))]
+        } else if history.queues.is_empty() {
+            vec![Line::from(Span::styled(
+                "No per-queue counters (waiting for `ethtool -S`, or not supported)",
+                Style::default().fg(app.theme.muted),
+            ))]
+        } else {
+            let rows = (area.height as usize).saturating_sub(2).max(1);
+            let columns = ((area.width as usize).saturating_sub(2) / CELL_WIDTH).max(1);
+            // 按列填充：同一列内编号连续，便于纵向比较
+            (0..rows.min(history.queues.len())).map(|row| {
+                let mut spans = Vec::new();
+                for queue in history.queues.iter().skip(row).step_by(rows).take(columns) {
+                    spans.extend([
+                        Span::styled(format!("{:<w$}", queue.name, w = NAME_WIDTH), Style::default().bold()),
+                        Span::styled("RX ", Style::default().fg(app.theme.rx)),
+                        Span::raw(format!("{:>w$}", format_speed(queue.rx, app.unit), w = SPEED_WIDTH)),
+                        Span::styled(" TX ", Style::default().fg(app.theme.tx)),
+                        Span::raw(format!("{:>w$}   ", format_speed(queue.tx, app.unit), w = SPEED_WIDTH)),
+                    ]);
+                }
+                Line::from(spans)
+            }).collect()
+        };
+
+        let block = port_block(app, index, &history, port_title(type_str, &history), title_color);
+
+        f.render_widget(Paragraph::new(text).block(block), area);
+    }
+}
+
+// 渲染单个端口的紧凑视图：无边框，RX / TX 各占一行
+// [名称列][RX/TX][sparkline][当前值]，选中的端口名称反色显示
+fn render_single_sparkline_item(app: &App, f: &mut Frame, area: Rect, index: usize) {
+    if let Some(history_lock) = app.histories.get(index)
+        && let Ok(history) = history_lock.read() {
+        let (type_str, border_color, rx_color, tx_color) = port_style(app, &history);
+        // 名称列较窄，断线标记放到第二行，避免把端口名挤掉
+        let name = format!("{} {}", type_str, history.name);
+        let (title, name_color) = alert_decoration(app, &history, name, border_color);
+        let mut name_style = stale_aware_style(app, &history, name_color).bold();
+        if app.focused.is_none() && app.selected == index {
+            name_style = name_style.reversed();
+        }
+
+        let spark_width = area.width.saturating_sub(SPARK_NAME_WIDTH + 3 + SPARK_VALUE_WIDTH) as usize;
+        let recent = |data: &std::collections::VecDeque<(f64, f64)>| -> Vec<u64> {
+            let skip = data.len().saturating_sub(spark_width);
+            data.iter().skip(skip).map(|(_, v)| *v as u64).collect()
+        };
+        let rx = recent(&history.rx_data);
+        let tx = recent(&history.tx_data);
+        // RX 与 TX 共用同一纵轴上限，两行的高度可以直接比较
+        let max = rx.iter().chain(tx.iter()).copied().max().unwrap_or(0).max(1);
+
+        let rows = [
+            ("RX ", rx, rx_color, history.rx_data.back()),
+            ("TX ", tx, tx_color, history.tx_data.back()),
+        ];
+        for (row, (label, data, color, last)) in rows.into_iter().enumerate().take(area.height as usize) {
+            let line_area = Rect { y: area.y + row as u16, height: 1, ..area };
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Length(SPARK_NAME_WIDTH),
+                    Constraint::Length(3),
+                    Constraint::Min(0),
+                    Constraint::Length(SPARK_VALUE_WIDTH),
+                ])
+                .split(line_area);
+
+            if row == 0 {
+                f.render_widget(Paragraph::new(Span::styled(title.clone(), name_style)), columns[0]);
+            } else if history.stale {
+                f.render_widget(Paragraph::new(Span::styled(STALE_SUFFIX.trim_start(), name_style)), columns[0]);
+            }
+            f.render_widget(Paragraph::new(Span::styled(label, Style::default().fg(color))), columns[1]);
+            f.render_widget(
+                Sparkline::default().data(&data).max(max).style(stale_aware_style(app, &history, color)),
+                columns[2],
+            );
+            let value = last.map(|(_, v)| *v).unwrap_or(0.0);
             f.render_widget(
-                Paragraph::new(text)
-                    .block(block)
-                    .alignment(Alignment::Left) // 表格模式下左对齐看起来更像列表
-                    .wrap(ratatui::widgets::Wrap { trim: true }), 
-                area
+                Paragraph::new(Span::styled(format_speed(value, app.unit), Style::default().bold()))
+                    .alignment(Alignment::Right),
+                columns[3],
             );
         }
     }
 }
 
-fn format_speed(bytes_per_sec: f64) -> String {
+fn format_bits_per_sec(bits_per_sec: f64) -> String {
+    if bits_per_sec < 1000.0 { return format!("{:.0} b/s", bits_per_sec); }
+    let kbits = bits_per_sec / 1000.0;
+    if kbits < 1000.0 { return format!("{:.1} Kb/s", kbits); }
+    let mbits = kbits / 1000.0;
+    if mbits < 1000.0 { return format!("{:.1} Mb/s", mbits); }
+    let gbits = mbits / 1000.0;
+    format!("{:.1} Gb/s", gbits)
+}
+
+fn format_bytes_per_sec(bytes_per_sec: f64) -> String {
     if bytes_per_sec < 1024.0 { return format!("{:.0} B/s", bytes_per_sec); }
     let kbytes = bytes_per_sec / 1024.0;
     if kbytes < 1024.0 { return format!("{:.1} KB/s", kbytes); }
@@ -208,4 +949,204 @@ fn format_speed(bytes_per_sec: f64) -> String {
     if mbytes < 1024.0 { return format!("{:.1} MB/s", mbytes); }
     let gbytes = mbytes / 1024.0;
     format!("{:.1} GB/s", gbytes)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{fake_history, test_app, TempTree};
+    use ratatui::{backend::TestBackend, Terminal};
+
+    /// 在 `width` 列宽的终端中渲染状态栏，返回可见的文本
+    fn footer_text(app: &App, width: u16) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(width, 1)).unwrap();
+        terminal.draw(|f| render_footer(app, f, f.area())).unwrap();
+        terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[tokio::test]
+    async fn footer_state_fits_on_a_normal_terminal() {
+        let tree = TempTree::new("footer");
+        tree.eth_port("eth0", true);
+        let app = test_app(&tree, &["--all", "--aggregate", "avg"]).await;
+        let text = footer_text(&app, 100);
+        assert!(text.contains("Units: Bytes/s"), "{}", text);
+        assert!(text.contains("Agg: Avg"), "{}", text);
+        assert!(text.contains("Sort: Name"), "{}", text);
+    }
+
+    #[test]
+    fn clock_labels_are_hh_mm_ss_with_a_placeholder() {
+        assert_eq!(format_clock(None), "--:--:--");
+        let label = format_clock(Some(1_700_000_000_123));
+        assert_eq!(label.len(), 8, "{}", label);
+        assert!(label.chars().enumerate().all(|(i, c)| if i == 2 || i == 5 { c == ':' } else { c.is_ascii_digit() }), "{}", label);
+    }
+
+    #[test]
+    fn intervals_use_the_coarsest_exact_unit() {
+        use std::time::Duration;
+        assert_eq!(format_interval(Duration::from_millis(10)), "10ms");
+        assert_eq!(format_interval(Duration::from_micros(1000)), "1ms");
+        assert_eq!(format_interval(Duration::from_micros(1500)), "1500us");
+        assert_eq!(format_interval(Duration::from_micros(250)), "250us");
+    }
+
+    #[test]
+    fn bytes_mode_steps_at_1024() {
+        assert_eq!(format_speed(0.0, SpeedUnit::Bytes), "0 B/s");
+        assert_eq!(format_speed(1023.0, SpeedUnit::Bytes), "1023 B/s");
+        assert_eq!(format_speed(1024.0, SpeedUnit::Bytes), "1.0 KB/s");
+        assert_eq!(format_speed(1024.0 * 1024.0, SpeedUnit::Bytes), "1.0 MB/s");
+        assert_eq!(format_speed(1024.0 * 1024.0 * 1024.0, SpeedUnit::Bytes), "1.0 GB/s");
+        assert_eq!(format_speed(12.5 * 1024.0 * 1024.0 * 1024.0, SpeedUnit::Bytes), "12.5 GB/s");
+    }
+
+    #[test]
+    fn bits_mode_steps_at_1000() {
+        // format_speed 的输入始终是 Bytes/s，bits 模式先 ×8
+        assert_eq!(format_speed(999.0 / 8.0, SpeedUnit::Bits), "999 b/s");
+        assert_eq!(format_speed(1000.0 / 8.0, SpeedUnit::Bits), "1.0 Kb/s");
+        assert_eq!(format_speed(999_900.0 / 8.0, SpeedUnit::Bits), "999.9 Kb/s");
+        assert_eq!(format_speed(1e6 / 8.0, SpeedUnit::Bits), "1.0 Mb/s");
+        assert_eq!(format_speed(999.9e6 / 8.0, SpeedUnit::Bits), "999.9 Mb/s");
+        assert_eq!(format_speed(1e9 / 8.0, SpeedUnit::Bits), "1.0 Gb/s");
+        assert_eq!(format_speed(100e9 / 8.0, SpeedUnit::Bits), "100.0 Gb/s");
+    }
+
+    #[test]
+    fn parse_speed_reads_bit_and_byte_suffixes() {
+        assert_eq!(parse_speed("90Gb/s", SpeedUnit::Bytes), Ok(90e9 / 8.0));
+        assert_eq!(parse_speed("1.5 Mbps", SpeedUnit::Bytes), Ok(1.5e6 / 8.0));
+        assert_eq!(parse_speed("800bits", SpeedUnit::Bytes), Ok(100.0));
+        assert_eq!(parse_speed("10GB/s", SpeedUnit::Bits), Ok(10.0 * 1024.0 * 1024.0 * 1024.0));
+        assert_eq!(parse_speed("500 KBps", SpeedUnit::Bits), Ok(500.0 * 1024.0));
+        assert_eq!(parse_speed("2B", SpeedUnit::Bits), Ok(2.0));
+    }
+
+    #[test]
+    fn parse_speed_bare_number_uses_default_unit() {
+        assert_eq!(parse_speed("500", SpeedUnit::Bytes), Ok(500.0));
+        assert_eq!(parse_speed("500", SpeedUnit::Bits), Ok(62.5));
+        // 只有倍率前缀时同样按默认单位解释
+        assert_eq!(parse_speed("1k", SpeedUnit::Bytes), Ok(1024.0));
+        assert_eq!(parse_speed(" 4M ", SpeedUnit::Bits), Ok(500_000.0));
+    }
+
+    #[test]
+    fn parse_speed_rejects_garbage() {
+        assert!(parse_speed("", SpeedUnit::Bytes).is_err());
+        assert!(parse_speed("fast", SpeedUnit::Bytes).is_err());
+        assert!(parse_speed("10 Gx", SpeedUnit::Bytes).is_err());
+        assert!(parse_speed("10 GiB/s", SpeedUnit::Bytes).is_err());
+    }
+
+    #[tokio::test]
+    async fn sparkline_view_fits_ports_that_charts_cannot() {
+        let tree = TempTree::new("sparkline");
+        tree.eth_port("eth0", true);
+        let mut app = test_app(&tree, &["--all"]).await;
+        app.histories = ["eth0", "eth1", "eth2"]
+            .iter()
+            .map(|name| fake_history(name, PortType::Ethernet, &[(1024.0, 2048.0)]))
+            .collect();
+        app.view_mode = ViewMode::Sparkline;
+        let mut terminal = Terminal::new(TestBackend::new(80, 10)).unwrap();
+        terminal.draw(|f| render(&mut app, f)).unwrap();
+        let text: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        for name in ["eth0", "eth1", "eth2"] {
+            assert!(text.contains(name), "{}", text);
+        }
+        assert!(text.contains("1.0 KB/s"), "{}", text);
+        assert!(text.contains("2.0 KB/s"), "{}", text);
+        assert_eq!(item_height(ViewMode::Sparkline), SPARKLINE_HEIGHT);
+        assert_eq!(item_height(ViewMode::Chart), CHART_HEIGHT);
+    }
+
+    #[tokio::test]
+    async fn empty_grid_names_the_ports_being_waited_for() {
+        let tree = TempTree::new("waiting");
+        let mut app = test_app(&tree, &["-i", "eth5"]).await;
+        let mut terminal = Terminal::new(TestBackend::new(60, 10)).unwrap();
+        terminal.draw(|f| render(&mut app, f)).unwrap();
+        let text: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("Waiting for eth5 to appear…"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn table_view_shows_totals_since_launch() {
+        let tree = TempTree::new("totals");
+        tree.eth_port("eth0", true);
+        let mut app = test_app(&tree, &["-i", "eth0"]).await;
+        app.view_mode = ViewMode::Table;
+        {
+            let mut h = app.histories[0].write().unwrap();
+            h.total_rx_bytes = 1536;
+            h.total_tx_bytes = 3 * 1024 * 1024 * 1024;
+            // 清空历史不影响累计流量
+            h.clear();
+        }
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal.draw(|f| render(&mut app, f)).unwrap();
+        let text: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("RX 1.50 KiB"), "{}", text);
+        assert!(text.contains("TX 3.00 GiB"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn queue_view_shows_why_queues_are_unavailable() {
+        let tree = TempTree::new("queues");
+        tree.eth_port("eth0", true);
+        let mut app = test_app(&tree, &["-i", "eth0"]).await;
+        app.view_mode = ViewMode::Queues;
+        app.histories[0].write().unwrap().queue_error = Some("`ethtool` not found in PATH".to_string());
+        let mut terminal = Terminal::new(TestBackend::new(80, 10)).unwrap();
+        terminal.draw(|f| render(&mut app, f)).unwrap();
+        let text: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("`ethtool` not found in PATH"), "{}", text);
+        assert!(!text.contains("waiting for `ethtool -S`"), "{}", text);
+    }
+
+    /// 所有面板都有面积，且高度不低于一行内容所需
+    fn assert_cells_usable(cells: &[(usize, Rect)], item_height: u16) {
+        for (_, rect) in cells {
+            assert!(rect.area() > 0, "{:?}", rect);
+            assert!(rect.height >= item_height.min(MIN_CELL_HEIGHT), "{:?}", rect);
+        }
+    }
+
+    #[test]
+    fn layout_grid_skips_cells_that_cannot_fit() {
+        for (area, item_height) in [
+            (Rect::new(0, 0, 19, 2), CHART_HEIGHT),
+            (Rect::new(0, 0, 19, 2), SPARKLINE_HEIGHT),
+            (Rect::new(0, 0, 1, 40), CHART_HEIGHT),
+            (Rect::new(0, 0, 1, 40), SPARKLINE_HEIGHT),
+            (Rect::new(3, 5, 80, 1), 1),
+        ] {
+            let cells = layout_grid(area, 0, 8, item_height);
+            assert_cells_usable(&cells, item_height);
+        }
+        // 宽度只够滚动条或为 0 时不产生任何面板
+        assert!(layout_grid(Rect::new(0, 0, 1, 40), 0, 8, CHART_HEIGHT).is_empty());
+        assert!(layout_grid(Rect::new(0, 0, 0, 40), 0, 8, CHART_HEIGHT).is_empty());
+        assert!(layout_grid(Rect::new(0, 0, 19, 2), 0, 8, CHART_HEIGHT).is_empty());
+        assert!(layout_grid(Rect::new(0, 0, 80, 0), 0, 8, 1).is_empty());
+    }
+
+    #[test]
+    fn layout_grid_truncates_last_cell_and_respects_start() {
+        let area = Rect::new(2, 1, 40, 2 * CHART_HEIGHT + MIN_CELL_HEIGHT);
+        let cells = layout_grid(area, 3, 10, CHART_HEIGHT);
+        assert_eq!(cells, vec![
+            (3, Rect::new(2, 1, 39, CHART_HEIGHT)),
+            (4, Rect::new(2, 1 + CHART_HEIGHT, 39, CHART_HEIGHT)),
+            (5, Rect::new(2, 1 + 2 * CHART_HEIGHT, 39, MIN_CELL_HEIGHT)),
+        ]);
+        // 截断后不足 MIN_CELL_HEIGHT 的面板被省略
+        let area = Rect { height: 2 * CHART_HEIGHT + MIN_CELL_HEIGHT - 1, ..area };
+        assert_eq!(layout_grid(area, 3, 10, CHART_HEIGHT).len(), 2);
+        // 端口数少于可放下的面板数
+        assert_eq!(layout_grid(area, 9, 10, CHART_HEIGHT).len(), 1);
+    }
+}
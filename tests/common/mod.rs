@@ -0,0 +1,61 @@
+//! 集成测试共用的 sysfs 夹具与进程辅助函数
+#![allow(dead_code)]
+
+mod sysfs;
+
+use std::io::{BufRead, BufReader};
+use std::process::{Child, ChildStdout, Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub use sysfs::TempTree;
+
+impl TempTree {
+    /// 以该夹具为 sysfs 根运行被测程序；配置目录指向夹具内不存在的路径，避免读到用户配置
+    pub fn dashboard(&self) -> Command {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_rdma-dashboard-rust"));
+        cmd.env("RDMA_DASH_SYSFS_ROOT", self.path())
+            .env("XDG_CONFIG_HOME", self.path().join("no-config"))
+            .env_remove("NO_COLOR")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        cmd
+    }
+}
+
+/// 向子进程发送 SIGINT
+pub fn interrupt(child: &Child) {
+    let status = Command::new("kill").args(["-INT", &child.id().to_string()]).status().unwrap();
+    assert!(status.success(), "kill -INT failed");
+}
+
+/// 等待子进程退出，超时则杀掉并判定失败
+pub fn wait_with_timeout(child: &mut Child, timeout: Duration) -> ExitStatus {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            return status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            panic!("process did not exit within {:?}", timeout);
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// 在后台线程中逐行读取 stdout，便于带超时地等待输出
+pub fn line_reader(stdout: ChildStdout) -> mpsc::Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
@@ -0,0 +1,79 @@
+//! 单元测试与集成测试共用的临时 sysfs 目录树
+//!
+//! 集成测试通过 `mod sysfs;` 引入，单元测试通过 `#[path]` 引入同一个文件。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// 临时目录中模拟的 sysfs 树，析构时删除
+pub struct TempTree {
+    root: PathBuf,
+}
+
+impl TempTree {
+    pub fn new(tag: &str) -> Self {
+        let root = std::env::temp_dir().join(format!(
+            "rdma-dashboard-test-{}-{}-{}",
+            tag,
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        Self { root }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    /// 写入文件 (自动创建父目录)，返回完整路径
+    pub fn write(&self, rel: &str, content: &str) -> PathBuf {
+        let path = self.root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    /// 原地覆盖为固定宽度的计数值，已打开的读取端不会读到截断后的空文件
+    pub fn set_counter(&self, rel: &str, value: u64) {
+        use std::os::unix::fs::FileExt;
+        let file = fs::OpenOptions::new().write(true).open(self.root.join(rel)).unwrap();
+        file.write_all_at(format!("{:020}\n", value).as_bytes(), 0).unwrap();
+    }
+
+    pub fn remove(&self, rel: &str) {
+        fs::remove_file(self.root.join(rel)).unwrap();
+    }
+
+    pub fn mkdir(&self, rel: &str) {
+        fs::create_dir_all(self.root.join(rel)).unwrap();
+    }
+
+    /// `class/infiniband/<dev>/ports/<port>/counters`，计数器全为 0
+    pub fn ib_port(&self, dev: &str, port: &str) {
+        let base = format!("class/infiniband/{}/ports/{}/counters", dev, port);
+        for counter in ["port_rcv_data", "port_xmit_data", "port_rcv_packets", "port_xmit_packets"] {
+            self.write(&format!("{}/{}", base, counter), "0\n");
+        }
+    }
+
+    /// `class/net/<dev>/statistics`，`physical` 为 true 时同时创建 `device`
+    pub fn eth_port(&self, dev: &str, physical: bool) {
+        if physical {
+            self.mkdir(&format!("class/net/{}/device", dev));
+        }
+        for counter in ["rx_bytes", "tx_bytes", "rx_packets", "tx_packets"] {
+            self.write(&format!("class/net/{}/statistics/{}", dev, counter), "0\n");
+        }
+    }
+}
+
+impl Drop for TempTree {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
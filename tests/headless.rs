@@ -0,0 +1,52 @@
+mod common;
+
+use common::{interrupt, line_reader, wait_with_timeout, TempTree};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 解析一行 NDJSON，返回按出现顺序排列的 (键, 值)；只支持本程序输出的扁平对象
+fn parse_object(line: &str) -> Vec<(String, String)> {
+    let body = line.strip_prefix('{').and_then(|l| l.strip_suffix('}')).expect("not a JSON object");
+    body.split(',')
+        .map(|pair| {
+            let (key, value) = pair.split_once(':').expect("missing ':'");
+            (key.trim_matches('"').to_string(), value.to_string())
+        })
+        .collect()
+}
+
+#[test]
+fn json_output_streams_ndjson_and_exits_on_sigint() {
+    let tree = TempTree::new("headless");
+    tree.ib_port("mlx5_0", "1");
+    tree.eth_port("eth0", true);
+
+    let mut child = tree.dashboard().args(["-a", "-o", "json"]).spawn().unwrap();
+    let lines = line_reader(child.stdout.take().unwrap());
+
+    let mut seen = Vec::new();
+    while seen.len() < 20 {
+        seen.push(lines.recv_timeout(TIMEOUT).expect("no NDJSON output"));
+    }
+    interrupt(&child);
+    let status = wait_with_timeout(&mut child, TIMEOUT);
+    assert!(status.success(), "exit status {:?}", status);
+
+    for line in &seen {
+        let fields = parse_object(line);
+        let keys: Vec<&str> = fields.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, ["iface", "type", "ts", "rx_bps", "tx_bps"], "line: {}", line);
+
+        match fields[0].1.as_str() {
+            "\"mlx5_0-1\"" => assert_eq!(fields[1].1, "\"rdma\""),
+            "\"eth0\"" => assert_eq!(fields[1].1, "\"ethernet\""),
+            other => panic!("unexpected iface {}", other),
+        }
+        let ts: u64 = fields[2].1.parse().expect("ts is not an integer");
+        assert!(ts > 1_600_000_000_000, "ts is not epoch milliseconds: {}", ts);
+        for (_, value) in &fields[3..] {
+            assert!(value.parse::<f64>().is_ok(), "rate is not a number: {}", value);
+        }
+    }
+}
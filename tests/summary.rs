@@ -0,0 +1,53 @@
+mod common;
+
+use common::{wait_with_timeout, TempTree};
+use std::io::Read;
+use std::thread;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 按两个以上空格切分表格行，单元格内部 (如 "2.0 KB/s") 只有单个空格
+fn cells(line: &str) -> Vec<&str> {
+    line.split("  ").map(str::trim).filter(|c| !c.is_empty()).collect()
+}
+
+#[test]
+fn once_prints_a_summary_table() {
+    let tree = TempTree::new("summary");
+    tree.ib_port("mlx5_0", "1");
+    tree.eth_port("eth0", true);
+    let rx = "class/net/eth0/statistics/rx_bytes";
+    tree.set_counter(rx, 0);
+
+    let mut child = tree.dashboard().args(["-a", "--once", "--duration", "2"]).spawn().unwrap();
+    // 采样中途产生 1 MiB 的接收流量
+    thread::sleep(Duration::from_millis(700));
+    tree.set_counter(rx, 1024 * 1024);
+
+    let status = wait_with_timeout(&mut child, TIMEOUT);
+    let mut stdout = String::new();
+    child.stdout.take().unwrap().read_to_string(&mut stdout).unwrap();
+    let mut stderr = String::new();
+    child.stderr.take().unwrap().read_to_string(&mut stderr).unwrap();
+    assert!(status.success(), "exit status {:?}, stderr: {}", status, stderr);
+
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 4, "{}", stdout);
+    assert!(lines[0].starts_with("Summary over 2."), "{}", lines[0]);
+    assert!(lines[0].ends_with("(Bytes/s)"), "{}", lines[0]);
+    assert_eq!(cells(lines[1]), [
+        "IFACE", "TYPE", "RX AVG", "RX MIN", "RX PEAK", "TX AVG", "TX MIN", "TX PEAK", "RX TOTAL", "TX TOTAL",
+    ]);
+
+    let eth = cells(lines[2]);
+    assert_eq!(eth.len(), 10, "{}", lines[2]);
+    assert_eq!(&eth[..2], ["eth0", "ethernet"]);
+    assert_eq!(&eth[8..], ["1.00 MiB", "0 B"]);
+    assert_ne!(eth[2], "0 B/s", "RX AVG");
+    assert_ne!(eth[4], "0 B/s", "RX PEAK");
+    assert_eq!(&eth[5..8], ["0 B/s", "0 B/s", "0 B/s"]);
+
+    // 没有流量的端口各项均为 0
+    assert_eq!(cells(lines[3]), ["mlx5_0-1", "rdma", "0 B/s", "0 B/s", "0 B/s", "0 B/s", "0 B/s", "0 B/s", "0 B", "0 B"]);
+}